@@ -2,7 +2,9 @@ use ansi_term::{ANSIGenericString, Color};
 use confy::ConfyError;
 use serde::{Deserialize, Serialize};
 
-use crate::{exact_math::float_mode::FloatMode, VERSION};
+use crate::{
+    exact_math::float_mode::FloatMode, DEFAULT_RECURSION_LIMIT, MAX_RECURSION_LIMIT, VERSION,
+};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Greeting {
@@ -22,6 +24,8 @@ pub struct Config {
     pub default_float_mode: String,
     pub greeting: Greeting,
     pub prompt: Prompt,
+    #[serde(default = "default_recursion_limit")]
+    pub recursion_limit: usize,
 }
 
 #[derive(Clone)]
@@ -32,6 +36,7 @@ pub struct Loaded<'a> {
     pub prompt: String,
     pub prompt_style: Color,
     pub float_mode: FloatMode,
+    pub recursion_limit: usize,
 }
 
 impl Default for Greeting {
@@ -60,10 +65,15 @@ impl Default for Config {
             default_float_mode: "exact".to_string(),
             greeting: Greeting::default(),
             prompt: Prompt::default(),
+            recursion_limit: default_recursion_limit(),
         }
     }
 }
 
+fn default_recursion_limit() -> usize {
+    DEFAULT_RECURSION_LIMIT
+}
+
 pub fn load() -> Result<Config, confy::ConfyError> {
     let cfg = confy::load("mini-calc", Some("mini-calc"));
     match cfg {
@@ -162,5 +172,6 @@ pub fn load_config<'a>(config: Config) -> Loaded<'a> {
         prompt: config.prompt.prompt,
         float_mode: load_float_mode(config.default_float_mode),
         prompt_style: load_color(config.prompt.prompt_color),
+        recursion_limit: config.recursion_limit.min(MAX_RECURSION_LIMIT),
     }
 }
@@ -0,0 +1,16 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub enum ComplexMode {
+    Rectangular,
+    Polar,
+}
+
+impl Display for ComplexMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rectangular => write!(f, "rectangular"),
+            Self::Polar => write!(f, "polar"),
+        }
+    }
+}
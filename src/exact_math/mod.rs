@@ -1,4 +1,7 @@
+pub mod complex_mode;
 pub mod float_mode;
+pub mod programmer_mode;
+pub mod radix_mode;
 pub mod rationals;
 pub mod scientific_mode;
 pub mod symbolic;
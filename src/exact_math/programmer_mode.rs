@@ -0,0 +1,248 @@
+use std::fmt::Display;
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+use crate::parsing::ast::Parameters;
+use crate::utils::integer_utils::from_big;
+use crate::PROGRAMMER_MODE;
+
+/// What happens when a result does not fit in the current integer type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Overflow {
+    Wrapping,
+    Saturating,
+    Checked,
+}
+
+/// A fixed-width integer type such as `u8` or `i64`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IntType {
+    pub bits: u32,
+    pub signed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProgrammerMode {
+    Off,
+    Fixed(IntType, Overflow),
+}
+
+impl IntType {
+    /// Parses `u8`, `i16`, ... up to `u128` and `i128`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let signed = match s.chars().next() {
+            Some('u') => false,
+            Some('i') => true,
+            _ => return None,
+        };
+        match s[1..].parse::<u32>() {
+            Ok(bits @ (8 | 16 | 32 | 64 | 128)) => Some(IntType { bits, signed }),
+            _ => None,
+        }
+    }
+
+    fn modulus(&self) -> BigInt {
+        BigInt::one() << self.bits
+    }
+
+    pub fn min(&self) -> BigInt {
+        if self.signed {
+            -(BigInt::one() << (self.bits - 1))
+        } else {
+            BigInt::zero()
+        }
+    }
+
+    pub fn max(&self) -> BigInt {
+        if self.signed {
+            (BigInt::one() << (self.bits - 1)) - 1
+        } else {
+            self.modulus() - 1
+        }
+    }
+
+    /// The two's complement bit pattern of `b`, as an unsigned integer.
+    pub fn to_bits(self, b: &BigInt) -> BigInt {
+        b.mod_floor(&self.modulus())
+    }
+
+    /// Brings `b` back in the range of the type following `overflow`.
+    pub fn fit(&self, b: BigInt, overflow: Overflow) -> Parameters {
+        if b >= self.min() && b <= self.max() {
+            return from_big(b);
+        }
+        match overflow {
+            Overflow::Wrapping => {
+                let bits = self.to_bits(&b);
+                if bits > self.max() {
+                    from_big(bits - self.modulus())
+                } else {
+                    from_big(bits)
+                }
+            }
+            Overflow::Saturating if b < self.min() => from_big(self.min()),
+            Overflow::Saturating => from_big(self.max()),
+            Overflow::Checked => Parameters::Identifier(format!("@{b} overflows {self}")),
+        }
+    }
+
+    /// Formats `b`, wrapped to the type, in decimal followed by its padded
+    /// two's complement hexadecimal and binary representations.
+    pub fn describe(&self, b: &BigInt) -> String {
+        let bits = self.to_bits(b);
+        let value = if bits > self.max() {
+            &bits - self.modulus()
+        } else {
+            bits.clone()
+        };
+        format!(
+            "{value} (0x{bits:0hex$x}, 0b{bits:0bin$b})",
+            hex = (self.bits / 4) as usize,
+            bin = self.bits as usize
+        )
+    }
+
+    /// What adding one to the largest value gives, `255+1=0 (0x00,
+    /// 0b00000000)` in u8 wrapping.
+    pub fn example(&self, overflow: Overflow) -> String {
+        let max = self.max();
+        let result = match self.fit(&max + 1, overflow) {
+            Parameters::Identifier(error) => error.trim_start_matches('@').to_string(),
+            p => match to_integer(&p) {
+                Some(b) => self.describe(&b),
+                None => p.to_string(),
+            },
+        };
+        format!("{max}+1={result}")
+    }
+}
+
+impl Display for IntType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", if self.signed { "i" } else { "u" }, self.bits)
+    }
+}
+
+impl Display for Overflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Wrapping => write!(f, "wrapping"),
+            Self::Saturating => write!(f, "saturating"),
+            Self::Checked => write!(f, "checked"),
+        }
+    }
+}
+
+impl Display for ProgrammerMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Off => write!(f, "off"),
+            Self::Fixed(t, o) => write!(f, "{t} {o}"),
+        }
+    }
+}
+
+fn to_integer(p: &Parameters) -> Option<BigInt> {
+    match p {
+        Parameters::Int(i) => Some(BigInt::from(*i)),
+        Parameters::Big(b) => Some(b.clone()),
+        _ => None,
+    }
+}
+
+/// Wraps an integer result following the current programmer mode.
+pub fn fit_to_mode(b: BigInt) -> Parameters {
+    PROGRAMMER_MODE.with(|pm| match &*pm.borrow() {
+        ProgrammerMode::Off => from_big(b),
+        ProgrammerMode::Fixed(t, o) => t.fit(b, *o),
+    })
+}
+
+/// Brings the integers of a value, those of a vector included, in the range
+/// of the current programmer mode, whatever operation or literal gave them.
+pub fn fit_parameter(p: Parameters) -> Parameters {
+    if PROGRAMMER_MODE.with(|pm| *pm.borrow() == ProgrammerMode::Off) {
+        return p;
+    }
+    match p {
+        Parameters::InterpreterVector(v) => Parameters::InterpreterVector(Box::from(
+            v.into_iter().map(fit_parameter).collect::<Vec<_>>(),
+        )),
+        p => match to_integer(&p) {
+            Some(b) => fit_to_mode(b),
+            None => p,
+        },
+    }
+}
+
+/// Computes `op` on two integers with the width of the current programmer
+/// mode, `None` when the mode is off or an operand is not an integer.
+pub fn fixed_width(
+    a: &Parameters,
+    b: &Parameters,
+    op: fn(BigInt, BigInt) -> BigInt,
+) -> Option<Parameters> {
+    if PROGRAMMER_MODE.with(|pm| *pm.borrow() == ProgrammerMode::Off) {
+        return None;
+    }
+    match (to_integer(a), to_integer(b)) {
+        (Some(a), Some(b)) => Some(fit_to_mode(op(a, b))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigInt;
+
+    use crate::parsing::ast::Parameters::*;
+
+    use super::{IntType, Overflow};
+
+    #[test]
+    pub fn test_parse() {
+        let u8 = IntType {
+            bits: 8,
+            signed: false,
+        };
+        assert_eq!(IntType::parse("u8"), Some(u8));
+        assert_eq!(IntType::parse("i12"), None);
+        assert_eq!(IntType::parse("x8"), None);
+    }
+
+    #[test]
+    pub fn test_fit() {
+        let u8 = IntType::parse("u8").unwrap();
+        let i8 = IntType::parse("i8").unwrap();
+        assert_eq!(u8.fit(BigInt::from(256), Overflow::Wrapping), Int(0));
+        assert_eq!(u8.fit(BigInt::from(-1), Overflow::Wrapping), Int(255));
+        assert_eq!(i8.fit(BigInt::from(128), Overflow::Wrapping), Int(-128));
+        assert_eq!(i8.fit(BigInt::from(200), Overflow::Saturating), Int(127));
+        assert_eq!(u8.fit(BigInt::from(-3), Overflow::Saturating), Int(0));
+        assert_eq!(
+            u8.fit(BigInt::from(300), Overflow::Checked),
+            Identifier("@300 overflows u8".to_string())
+        );
+    }
+
+    #[test]
+    pub fn test_describe() {
+        let i8 = IntType::parse("i8").unwrap();
+        assert_eq!(i8.describe(&BigInt::from(-1)), "-1 (0xff, 0b11111111)");
+        assert_eq!(i8.describe(&BigInt::from(5)), "5 (0x05, 0b00000101)");
+        assert_eq!(i8.describe(&BigInt::from(255)), "-1 (0xff, 0b11111111)");
+    }
+
+    #[test]
+    pub fn test_example() {
+        let u8 = IntType::parse("u8").unwrap();
+        let i16 = IntType::parse("i16").unwrap();
+        assert_eq!(u8.example(Overflow::Wrapping), "255+1=0 (0x00, 0b00000000)");
+        assert_eq!(
+            i16.example(Overflow::Checked),
+            "32767+1=32768 overflows i16"
+        );
+    }
+}
@@ -0,0 +1,20 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub enum RadixMode {
+    Decimal,
+    Hexadecimal,
+    Binary,
+    Octal,
+}
+
+impl Display for RadixMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Decimal => write!(f, "decimal"),
+            Self::Hexadecimal => write!(f, "hexadecimal"),
+            Self::Binary => write!(f, "binary"),
+            Self::Octal => write!(f, "octal"),
+        }
+    }
+}
@@ -1,89 +1,102 @@
-use std::{fmt::Display, ops};
+use std::{cmp::Ordering, fmt::Display, ops, str::FromStr};
 
-use crate::{utils::integer_utils::gcd, FLOAT_MODE};
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::{
+    utils::integer_utils::{big_to_f64, checked_pow},
+    FLOAT_MODE,
+};
 
 use super::{float_mode::FloatMode, scientific_mode::from_float};
 
-#[derive(Debug, Clone, Copy)]
+/// Exact fraction `over / under`, both sides are arbitrary precision integers
+/// so that exact computations never overflow.
+#[derive(Debug, Clone)]
 pub struct Rationals {
-    pub under: i64,
-    pub over: i64,
+    pub under: BigInt,
+    pub over: BigInt,
 }
 
 impl Rationals {
-    pub fn put_to_denominator(&self, n: i64) -> Self {
+    pub fn put_to_denominator(&self, n: &BigInt) -> Self {
         Rationals {
-            under: self.under * n,
-            over: self.over * n,
+            under: &self.under * n,
+            over: &self.over * n,
         }
     }
     pub fn new(under: i64, over: i64) -> Self {
+        Rationals {
+            under: BigInt::from(under),
+            over: BigInt::from(over),
+        }
+    }
+
+    pub fn from_big(under: BigInt, over: BigInt) -> Self {
         Rationals { under, over }
     }
 
-    pub fn approx(self) -> f64 {
-        return self.over as f64 / self.under as f64;
+    pub fn approx(&self) -> f64 {
+        let (over, under) = (big_to_f64(&self.over), big_to_f64(&self.under));
+        if over.is_finite() && under.is_finite() {
+            return over / under;
+        }
+        // both sides are shifted so that they fit in a f64 again
+        let shift = self.over.bits().max(self.under.bits()).saturating_sub(1000);
+        big_to_f64(&(&self.over >> shift)) / big_to_f64(&(&self.under >> shift))
     }
 
+    /// Converts a float to the rational written by its shortest decimal
+    /// representation, so that `1e-3` becomes exactly `1/1000`. Infinities
+    /// and NaN have none, callers must keep them as floats.
     pub fn rationalize(f: f64) -> Self {
-        let r = (f * (10.0_f64.powf(10.0))).round() as i64;
-        Rationals::new(10_i64.pow(10), r).reduce()
+        if !f.is_finite() {
+            return Rationals::new(1, i64::MAX);
+        }
+        let repr = format!("{:e}", f);
+        let (mantissa, exponent) = repr.split_once('e').unwrap_or((&repr, "0"));
+        let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let digits = BigInt::from_str(&(whole.to_owned() + fraction)).unwrap_or_default();
+        let scale = exponent.parse::<i32>().unwrap_or(0) - fraction.len() as i32;
+        let power = num_traits::pow(BigInt::from(10), scale.unsigned_abs() as usize);
+        if scale >= 0 {
+            Rationals::from_big(BigInt::one(), digits * power)
+        } else {
+            Rationals::from_big(power, digits).reduce()
+        }
     }
 
-    pub fn is_null(self) -> bool {
-        return self.over == 0;
+    pub fn is_null(&self) -> bool {
+        return self.over.is_zero();
     }
 
     pub fn opposite(self) -> Self {
-        Rationals::new(self.under, -1 * self.over)
+        Rationals::from_big(self.under, -self.over)
     }
 
     pub fn invert(self) -> Result<Rationals, Rationals> {
-        match self.over {
-            0 => Err(Rationals::new(0, 1)),
-            _ => Ok(Rationals::new(self.over, self.under).reduce()),
+        match self.over.is_zero() {
+            true => Err(Rationals::new(0, 1)),
+            false => Ok(Rationals::from_big(self.over, self.under).reduce()),
         }
     }
 
     pub fn reduce(self) -> Self {
-        let minus;
-        let i1;
-        let i2;
-        if self.over < 0 && self.under > 0 {
-            minus = true;
-            i1 = -self.over;
-            i2 = self.under;
-        } else if self.over > 0 && self.under < 0 {
-            minus = true;
-            i1 = self.over;
-            i2 = -self.under;
-        } else if self.over < 0 && self.under < 0 {
-            minus = false;
-            i1 = -self.over;
-            i2 = -self.under;
+        let minus = self.over.is_negative() != self.under.is_negative();
+        let i1 = self.over.abs();
+        let i2 = self.under.abs();
+
+        if i1.is_zero() && i2.is_zero() {
+            return Rationals::new(0, 0);
+        } else if i1.is_zero() {
+            return Rationals::new(1, 0);
+        } else if i2.is_zero() {
+            return Rationals::new(1, i64::MAX);
         } else {
-            minus = false;
-            i1 = self.over;
-            i2 = self.under;
-        }
-
-        if i1 == 0 && i2 == 0 {
-            return Rationals { under: 0, over: 0 };
-        } else if i1 == 0 {
-            return Rationals { under: 1, over: 0 };
-        } else if i2 == 0 {
-            return Rationals {
-                under: 1,
-                over: i64::MAX,
-            };
-        } else {
-            let gcd = gcd(i1, i2);
-            let new_under = self.under.abs() / gcd;
-            let new_over = if minus {
-                -self.over.abs() / gcd
-            } else {
-                self.over.abs() / gcd
-            };
+            let gcd = i1.gcd(&i2);
+            let new_under = i2 / &gcd;
+            let new_over = if minus { -(i1 / &gcd) } else { i1 / &gcd };
             return Rationals {
                 under: new_under,
                 over: new_over,
@@ -91,14 +104,47 @@ impl Rationals {
         }
     }
     pub fn abs(self) -> Self {
-        Rationals::new(self.under.abs(), self.over.abs())
+        Rationals::from_big(self.under.abs(), self.over.abs())
+    }
+
+    /// Exact integer power, `None` when the result is too large or when a zero
+    /// is raised to a negative power.
+    pub fn pow(&self, exponent: &BigInt) -> Option<Rationals> {
+        let n = exponent.abs().to_u32()?;
+        let r = Rationals::from_big(checked_pow(&self.under, n)?, checked_pow(&self.over, n)?);
+        if exponent.is_negative() {
+            r.invert().ok()
+        } else {
+            Some(r.reduce())
+        }
+    }
+
+    /// Exact `n`-th root, `None` when the rational is not a perfect power.
+    pub fn root(&self, n: u32) -> Option<Rationals> {
+        let fs = self.clone().reduce();
+        if n == 0 || (n.is_multiple_of(2) && fs.over.is_negative()) {
+            return None;
+        }
+        let over = fs.over.nth_root(n);
+        let under = fs.under.nth_root(n);
+        if over.pow(n) == fs.over && under.pow(n) == fs.under {
+            Some(Rationals::from_big(under, over))
+        } else {
+            None
+        }
+    }
+}
+
+impl From<BigInt> for Rationals {
+    fn from(value: BigInt) -> Self {
+        Rationals::from_big(BigInt::one(), value)
     }
 }
 
 impl Display for Rationals {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let fs = self.reduce();
-        if fs.under == 1 {
+        let fs = self.clone().reduce();
+        if fs.under.is_one() {
             write!(f, "{}", fs.over)
         } else {
             FLOAT_MODE.with(|fm| match *fm.borrow() {
@@ -115,50 +161,20 @@ impl PartialEq for Rationals {
         if self.under == other.under {
             return self.over == other.over;
         } else {
-            let i1 = self.put_to_denominator(other.under);
-            let i2 = other.put_to_denominator(self.under);
+            let i1 = self.put_to_denominator(&other.under);
+            let i2 = other.put_to_denominator(&self.under);
             return i1.over == i2.over;
         }
     }
-    fn ne(&self, other: &Self) -> bool {
-        return !self.eq(other);
-    }
 }
 
 impl PartialOrd for Rationals {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        if !(self.under == other.under) {
-            if self.over == other.over {
-                return Some(std::cmp::Ordering::Equal);
-            }
-            if self.over > other.over {
-                return Some(std::cmp::Ordering::Greater);
-            }
-            if self.over < other.over {
-                return Some(std::cmp::Ordering::Less);
-            }
-            None
-        } else {
-            let i1 = self.put_to_denominator(other.under);
-            let i2 = other.put_to_denominator(self.under);
-            if i1.over == i2.over {
-                return Some(std::cmp::Ordering::Equal);
-            }
-            if i1.over > i2.over {
-                return Some(std::cmp::Ordering::Greater);
-            }
-            if i1.over < i2.over {
-                return Some(std::cmp::Ordering::Less);
-            }
-            None
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let (f1, f2) = (self.clone().reduce(), other.clone().reduce());
+        if f1.under.is_zero() || f2.under.is_zero() {
+            return None;
         }
-    }
-
-    fn le(&self, other: &Self) -> bool {
-        return self < other || self == other;
-    }
-    fn ge(&self, other: &Self) -> bool {
-        return self > other || self == other;
+        Some((&f1.over * &f2.under).cmp(&(&f2.over * &f1.under)))
     }
 }
 
@@ -166,11 +182,11 @@ impl ops::Add for Rationals {
     type Output = Rationals;
     fn add(self, rhs: Self) -> Self::Output {
         if self.under == rhs.under {
-            Rationals::new(self.under, self.over + rhs.over).reduce()
+            Rationals::from_big(self.under, self.over + rhs.over).reduce()
         } else {
-            let f1 = self.put_to_denominator(rhs.under);
-            let f2 = rhs.put_to_denominator(self.under);
-            Rationals::new(f1.under, f1.over + f2.over).reduce()
+            let f1 = self.put_to_denominator(&rhs.under);
+            let f2 = rhs.put_to_denominator(&self.under);
+            Rationals::from_big(f1.under, f1.over + f2.over).reduce()
         }
     }
 }
@@ -178,14 +194,14 @@ impl ops::Add for Rationals {
 impl ops::Sub for Rationals {
     type Output = Rationals;
     fn sub(self, rhs: Self) -> Self::Output {
-        return self + Rationals::new(rhs.under, -rhs.over);
+        return self + Rationals::from_big(rhs.under, -rhs.over);
     }
 }
 
 impl ops::Mul for Rationals {
     type Output = Rationals;
     fn mul(self, rhs: Self) -> Self::Output {
-        return Rationals::new(self.under * rhs.under, self.over * rhs.over).reduce();
+        return Rationals::from_big(self.under * rhs.under, self.over * rhs.over).reduce();
     }
 }
 
@@ -194,19 +210,21 @@ impl ops::Div for Rationals {
     fn div(self, rhs: Self) -> Self::Output {
         let l = self.under * rhs.over;
         let rs = self.over * rhs.under;
-        let r = Rationals::new(l, rs);
+        let r = Rationals::from_big(l, rs);
         return r.reduce();
     }
 }
 
 #[cfg(test)]
 mod test {
+    use num_bigint::BigInt;
+
     use super::Rationals;
 
     #[test]
     pub fn test_denominator() {
         let expected = Rationals::new(10, 30);
-        let result = Rationals::new(2, 6).put_to_denominator(5);
+        let result = Rationals::new(2, 6).put_to_denominator(&BigInt::from(5));
         assert_eq!(expected, result);
     }
 
@@ -298,4 +316,43 @@ mod test {
         let value = Rationals::new(25, 32) / Rationals::new(76, 42);
         assert_eq!(value, expected);
     }
+
+    #[test]
+    pub fn test_add_no_overflow() {
+        let expected = Rationals::from_big(
+            BigInt::from(i64::MAX) * (i64::MAX - 1),
+            BigInt::from(i64::MAX) * 2 - 1,
+        );
+        let value = Rationals::new(i64::MAX, 1) + Rationals::new(i64::MAX - 1, 1);
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    pub fn test_mult_no_overflow() {
+        let expected = Rationals::from_big(BigInt::from(i64::MAX) * i64::MAX, BigInt::from(1));
+        let value = Rationals::new(i64::MAX, 1) * Rationals::new(i64::MAX, 1);
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    pub fn test_pow_negative() {
+        let expected = Rationals::new(8, 27);
+        let value = Rationals::new(3, 2).pow(&BigInt::from(-3));
+        assert_eq!(value, Some(expected));
+    }
+
+    #[test]
+    pub fn test_root() {
+        assert_eq!(Rationals::new(9, 4).root(2), Some(Rationals::new(3, 2)));
+        assert_eq!(Rationals::new(1, 2).root(2), None);
+    }
+
+    #[test]
+    pub fn test_rationalize() {
+        assert_eq!(
+            Rationals::rationalize(1e-12),
+            Rationals::new(1_000_000_000_000, 1)
+        );
+        assert_eq!(Rationals::rationalize(-2.5), Rationals::new(2, -5));
+    }
 }
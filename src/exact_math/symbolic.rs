@@ -5,6 +5,7 @@ pub fn size(p: &Parameters) -> i32 {
     match p {
         Null
         | Int(_)
+        | Big(_)
         | Str(_)
         | Float(_)
         | Identifier(_)
@@ -19,11 +20,27 @@ pub fn size(p: &Parameters) -> i32 {
         | LesserOperation
         | LesserOrEqualOperation
         | Equal
+        | NotEqual
         | Bool(_)
         | Rational(_)
+        | Complex(_)
         | OrOperation
         | AndOperation
+        | BitAndOperation
+        | BitOrOperation
+        | XorOperation
+        | BitNotOperation
+        | ShiftLeftOperation
+        | ShiftRightOperation
+        | ModOperation
+        | FloorDivideOperation
         | Not
+        | CompoundAssign(_)
+        | Delete(_)
+        | Memo(_)
+        | Variadic(_)
+        | When
+        | Lambda(_)
         | Vector(_)
         | InterpreterVector(_) => 0,
         Plus(x, y) => 1 + size(x) + size(y),
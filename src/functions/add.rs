@@ -1,3 +1,4 @@
+use crate::exact_math::programmer_mode::fixed_width;
 use crate::exact_math::rationals::Rationals;
 use crate::exact_math::symbolic::size;
 use crate::functions::function::apply_operator;
@@ -5,12 +6,18 @@ use crate::functions::function::apply_operator_reverse;
 use crate::parsing::ast;
 use crate::parsing::ast::Parameters::*;
 use crate::parsing::ast::*;
+use crate::utils::complex_utils::from_complex;
+use crate::utils::integer_utils::{big_to_f64, from_big};
+use num_bigint::BigInt;
 
 use super::mult::mult;
 
 pub type ORam<'a> = Option<&'a ast::Ram>;
 
 pub fn add(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
+    if let Some(r) = fixed_width(&i, &i2, |a, b| a + b) {
+        return r;
+    }
     match (i, i2) {
         (Null, Int(v)) => Int(v),
         (Null, Float(f)) => Float(f),
@@ -25,7 +32,29 @@ pub fn add(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
         (Int(i), Rational(s)) => Rational(s + Rationals::new(1, i)),
         (Rational(s), Float(f)) => Float(s.approx() + f),
         (Float(f), Rational(s)) => Float(f + s.approx()),
-        (Int(v), Int(v2)) => Int(v + v2),
+        (Int(v), Int(v2)) => match v.checked_add(v2) {
+            Some(r) => Int(r),
+            None => from_big(BigInt::from(v) + v2),
+        },
+        (Complex(c), Complex(c2)) => from_complex(c + c2),
+        (Complex(c), Int(i)) | (Int(i), Complex(c)) => from_complex(c + i as f64),
+        (Complex(c), Float(f)) | (Float(f), Complex(c)) => from_complex(c + f),
+        (Complex(c), Rational(s)) | (Rational(s), Complex(c)) => from_complex(c + s.approx()),
+        (Complex(c), Big(v)) | (Big(v), Complex(c)) => from_complex(c + big_to_f64(&v)),
+        (Complex(c), Null) | (Null, Complex(c)) => Complex(c),
+        (Complex(c), Identifier(s)) | (Identifier(s), Complex(c)) => match ram {
+            None => Plus(Box::from(Identifier(s)), Box::from(Complex(c))),
+            Some(_) => apply_operator(Identifier(s), Complex(c), ram, add),
+        },
+        (Big(v), Big(v2)) => from_big(v + v2),
+        (Big(v), Int(i)) | (Int(i), Big(v)) => from_big(v + i),
+        (Big(v), Float(f)) | (Float(f), Big(v)) => Float(big_to_f64(&v) + f),
+        (Big(v), Rational(s)) | (Rational(s), Big(v)) => Rational(s + Rationals::from(v)),
+        (Big(v), Null) | (Null, Big(v)) => Big(v),
+        (Big(v), Identifier(s)) | (Identifier(s), Big(v)) => match ram {
+            None => Plus(Box::from(Identifier(s)), Box::from(Big(v))),
+            Some(_) => apply_operator(Identifier(s), Big(v), ram, add),
+        },
         (Int(v), Float(f)) => Float((v as f64) + f),
         (Float(v), Float(f)) => Float(v + f),
         (Float(v), Int(i1)) => Float(v + (i1 as f64)),
@@ -206,7 +235,10 @@ pub fn add(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
         }
 
         (Plus(s1, s2), Rational(r)) => {
-            let first = Plus(Box::from(add(*s1.clone(), Rational(r), ram)), s2.clone());
+            let first = Plus(
+                Box::from(add(*s1.clone(), Rational(r.clone()), ram)),
+                s2.clone(),
+            );
             let second = Plus(s1.clone(), Box::from(add(*s2.clone(), Rational(r), ram)));
 
             let (s1, s2) = (size(&first), size(&second));
@@ -218,7 +250,10 @@ pub fn add(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
         }
 
         (Rational(r), Plus(s1, s2)) => {
-            let first = Plus(Box::from(add(*s1.clone(), Rational(r), ram)), s2.clone());
+            let first = Plus(
+                Box::from(add(*s1.clone(), Rational(r.clone()), ram)),
+                s2.clone(),
+            );
             let second = Plus(s1.clone(), Box::from(add(*s2.clone(), Rational(r), ram)));
 
             let (s1, s2) = (size(&first), size(&second));
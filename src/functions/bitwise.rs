@@ -0,0 +1,169 @@
+use num_bigint::BigInt;
+use num_traits::{One, ToPrimitive};
+
+use crate::exact_math::programmer_mode::fit_to_mode;
+use crate::parsing::ast::Parameters;
+use crate::parsing::ast::Parameters::*;
+
+use super::add::ORam;
+use super::function::{apply_operator, apply_operator_reverse};
+
+/// Largest shift accepted by `<<`, anything above would allocate huge integers.
+const MAX_SHIFT: u64 = 1 << 20;
+
+/// Reads an exact integer out of a parameter, rationals are accepted when
+/// their denominator is one.
+fn to_integer(p: &Parameters) -> Option<BigInt> {
+    match p {
+        Int(i) => Some(BigInt::from(*i)),
+        Big(b) => Some(b.clone()),
+        Rational(r) if r.under.is_one() => Some(r.over.clone()),
+        _ => None,
+    }
+}
+
+fn bitwise(
+    i: Parameters,
+    i2: Parameters,
+    ram: ORam,
+    this: fn(Parameters, Parameters, ORam) -> Parameters,
+    on_integers: fn(BigInt, BigInt) -> Parameters,
+    on_bools: fn(bool, bool) -> bool,
+) -> Parameters {
+    match (i, i2) {
+        (Null, p) | (p, Null) => p,
+        (Bool(b), Bool(b2)) => Bool(on_bools(b, b2)),
+        (Identifier(s), p) => match ram {
+            Some(_) => apply_operator(Identifier(s), p, ram, this),
+            None => Identifier(s),
+        },
+        (p, Identifier(s)) => match ram {
+            Some(_) => apply_operator_reverse(p, Identifier(s), ram, this),
+            None => Identifier(s),
+        },
+        (p, p2) => match (to_integer(&p), to_integer(&p2)) {
+            (Some(a), Some(b)) => on_integers(a, b),
+            _ => Identifier("@bitwise operators only apply to integers".to_string()),
+        },
+    }
+}
+
+pub fn bit_and(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
+    bitwise(
+        i,
+        i2,
+        ram,
+        bit_and,
+        |a, b| fit_to_mode(a & b),
+        |a, b| a && b,
+    )
+}
+
+pub fn bit_or(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
+    bitwise(i, i2, ram, bit_or, |a, b| fit_to_mode(a | b), |a, b| a || b)
+}
+
+pub fn xor(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
+    bitwise(i, i2, ram, xor, |a, b| fit_to_mode(a ^ b), |a, b| a != b)
+}
+
+fn shift_amount(b: &BigInt) -> Result<u64, Parameters> {
+    match b.to_u64() {
+        Some(n) if n <= MAX_SHIFT => Ok(n),
+        Some(_) => Err(Identifier("@shift amount is too large".to_string())),
+        None => Err(Identifier(
+            "@shift amount must be a non-negative integer".to_string(),
+        )),
+    }
+}
+
+pub fn shift_left(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
+    bitwise(
+        i,
+        i2,
+        ram,
+        shift_left,
+        |a, b| match shift_amount(&b) {
+            Ok(n) => fit_to_mode(a << n),
+            Err(e) => e,
+        },
+        |_, _| false,
+    )
+}
+
+pub fn shift_right(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
+    bitwise(
+        i,
+        i2,
+        ram,
+        shift_right,
+        // an arithmetic shift, rounding toward negative infinity like floor(a / 2^n)
+        |a, b| match b.to_u64() {
+            Some(n) => fit_to_mode(a >> n),
+            None => Identifier("@shift amount must be a non-negative integer".to_string()),
+        },
+        |_, _| false,
+    )
+}
+
+/// Unary `~x`, the right hand side is always `Null`.
+pub fn bit_not(i: Parameters, _i2: Parameters, ram: ORam) -> Parameters {
+    match i {
+        Bool(b) => Bool(!b),
+        Identifier(s) => match ram {
+            Some(_) => apply_operator(Identifier(s), Null, ram, bit_not),
+            None => Identifier(s),
+        },
+        p => match to_integer(&p) {
+            Some(a) => fit_to_mode(!a),
+            None => Identifier("@bitwise operators only apply to integers".to_string()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::exact_math::rationals::Rationals;
+    use crate::parsing::ast::Parameters::*;
+    use num_bigint::BigInt;
+
+    use super::{bit_and, bit_not, bit_or, shift_left, shift_right, xor};
+
+    #[test]
+    pub fn test_bit_and_or_xor() {
+        assert_eq!(bit_and(Int(12), Int(10), None), Int(8));
+        assert_eq!(bit_or(Int(12), Int(10), None), Int(14));
+        assert_eq!(xor(Int(12), Int(10), None), Int(6));
+        assert_eq!(bit_and(Int(-1), Int(0xff), None), Int(0xff));
+    }
+
+    #[test]
+    pub fn test_bit_not() {
+        assert_eq!(bit_not(Int(0), Null, None), Int(-1));
+        assert_eq!(bit_not(Int(5), Null, None), Int(-6));
+        assert_eq!(bit_not(Bool(true), Null, None), Bool(false));
+    }
+
+    #[test]
+    pub fn test_shift() {
+        assert_eq!(shift_left(Int(1), Int(4), None), Int(16));
+        assert_eq!(shift_right(Int(-9), Int(1), None), Int(-5));
+        assert_eq!(
+            shift_left(Int(1), Int(64), None),
+            Big(BigInt::from(1) << 64)
+        );
+        assert_eq!(
+            shift_left(Int(1), Int(-1), None),
+            Identifier("@shift amount must be a non-negative integer".to_string())
+        );
+    }
+
+    #[test]
+    pub fn test_bitwise_rationals() {
+        assert_eq!(bit_or(Rational(Rationals::new(1, 4)), Int(1), None), Int(5));
+        assert_eq!(
+            bit_and(Rational(Rationals::new(2, 1)), Int(1), None),
+            Identifier("@bitwise operators only apply to integers".to_string())
+        );
+    }
+}
@@ -1,21 +1,85 @@
+use crate::exact_math::programmer_mode::fixed_width;
 use crate::exact_math::rationals::Rationals;
 use crate::exact_math::symbolic::size;
 use crate::functions::function::apply_operator;
 use crate::functions::function::apply_operator_reverse;
 use crate::parsing::ast::Parameters::*;
 use crate::parsing::ast::*;
+use crate::utils::complex_utils::from_complex;
+use crate::utils::integer_utils::{big_to_f64, from_big};
+use num_bigint::BigInt;
+use num_traits::Zero;
 
 use super::add::add;
 use super::add::ORam;
 use super::mult::mult;
 
+/// Division of big integers, it stays an integer whenever the division is
+/// exact and gives a rational otherwise.
+fn divide_big(v: BigInt, v2: BigInt) -> Parameters {
+    if v2.is_zero() {
+        Float(big_to_f64(&v) / 0.0)
+    } else if (&v % &v2).is_zero() {
+        from_big(v / v2)
+    } else {
+        Rational(Rationals::from_big(v2, v).reduce())
+    }
+}
+
 pub fn divide(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
+    // fixed-width integers divide like in C, 7/2 is 3
+    let by_zero = matches!(i2, Int(0)) || matches!(&i2, Big(b) if b.is_zero());
+    if !by_zero {
+        if let Some(r) = fixed_width(&i, &i2, |a, b| a / b) {
+            return r;
+        }
+    }
     match (i, i2) {
         (Null, Int(v)) => Int(v),
         (Null, Float(f)) => Float(f),
         (Int(v), Null) => Int(v),
         (Float(f), Null) => Float(f),
         (Int(v), Int(v2)) => Rational(Rationals::new(v2, v)),
+        (Complex(c), Complex(c2)) => from_complex(c / c2),
+        (Complex(c), Int(i)) => from_complex(c / i as f64),
+        (Int(i), Complex(c)) => from_complex(i as f64 / c),
+        (Complex(c), Float(f)) => from_complex(c / f),
+        (Float(f), Complex(c)) => from_complex(f / c),
+        (Complex(c), Rational(s)) => from_complex(c / s.approx()),
+        (Rational(s), Complex(c)) => from_complex(s.approx() / c),
+        (Complex(c), Big(v)) => from_complex(c / big_to_f64(&v)),
+        (Big(v), Complex(c)) => from_complex(big_to_f64(&v) / c),
+        (Complex(c), Null) | (Null, Complex(c)) => Complex(c),
+        (Complex(c), Identifier(s)) => match ram {
+            None => Div(
+                Box::from(Complex(c)),
+                Box::from(Var(Box::from(Int(1)), 1, s)),
+            ),
+            Some(_) => apply_operator_reverse(Complex(c), Identifier(s), ram, divide),
+        },
+        (Identifier(s), Complex(c)) => match ram {
+            None => Div(
+                Box::from(Var(Box::from(Int(1)), 1, s)),
+                Box::from(Complex(c)),
+            ),
+            Some(_) => apply_operator(Identifier(s), Complex(c), ram, divide),
+        },
+        (Big(v), Big(v2)) => divide_big(v, v2),
+        (Big(v), Int(i)) => divide_big(v, BigInt::from(i)),
+        (Int(i), Big(v)) => divide_big(BigInt::from(i), v),
+        (Big(v), Null) | (Null, Big(v)) => Big(v),
+        (Big(v), Float(f)) => Float(big_to_f64(&v) / f),
+        (Float(f), Big(v)) => Float(f / big_to_f64(&v)),
+        (Big(v), Rational(s)) => Rational(Rationals::from(v) / s),
+        (Rational(s), Big(v)) => Rational(s / Rationals::from(v)),
+        (Big(v), Identifier(s)) => match ram {
+            None => Div(Box::from(Big(v)), Box::from(Var(Box::from(Int(1)), 1, s))),
+            Some(_) => apply_operator_reverse(Big(v), Identifier(s), ram, divide),
+        },
+        (Identifier(s), Big(v)) => match ram {
+            None => Div(Box::from(Var(Box::from(Int(1)), 1, s)), Box::from(Big(v))),
+            Some(_) => apply_operator(Identifier(s), Big(v), ram, divide),
+        },
         (Int(v), Float(f)) => Float((v as f64) / f),
         (Float(v), Float(f)) => Float(v / f),
         (Float(v), Int(i1)) => Float(v / (i1 as f64)),
@@ -49,8 +113,8 @@ pub fn divide(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
         (Rational(r), Identifier(ss)) => match ram {
             Some(_) => apply_operator_reverse(Rational(r), Identifier(ss.clone()), ram, divide),
             None => Div(
-                Box::from(Int(r.over)),
-                Box::from(Var(Box::from(Int(r.under)), 1, ss.clone())),
+                Box::from(from_big(r.over.clone())),
+                Box::from(Var(Box::from(from_big(r.under.clone())), 1, ss.clone())),
             ),
         },
         (Identifier(ss), Rational(r)) => match ram {
@@ -102,9 +166,9 @@ pub fn divide(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
         },
         (Float(i), Identifier(s)) => match ram {
             None => Div(
-                Box::from(Int(Rationals::rationalize(i).over)),
+                Box::from(from_big(Rationals::rationalize(i).over)),
                 Box::from(Var(
-                    Box::from(Int(Rationals::rationalize(i).under)),
+                    Box::from(from_big(Rationals::rationalize(i).under)),
                     1,
                     s.clone(),
                 )),
@@ -192,7 +256,7 @@ pub fn divide(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
 
         (Plus(s1, s2), Rational(r)) => {
             let first = add(
-                divide(*s1.clone(), Rational(r), ram),
+                divide(*s1.clone(), Rational(r.clone()), ram),
                 divide(*s2.clone(), Rational(r), ram),
                 ram,
             );
@@ -386,7 +450,11 @@ pub fn divide(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
         }
 
         (Mul(s1, s2), Rational(r)) => {
-            let first = mult(divide(*s1.clone(), Rational(r), ram), *s2.clone(), ram);
+            let first = mult(
+                divide(*s1.clone(), Rational(r.clone()), ram),
+                *s2.clone(),
+                ram,
+            );
             let second = mult(*s1.clone(), divide(*s2.clone(), Rational(r), ram), ram);
 
             let (ss1, ss2) = (size(&first), size(&second));
@@ -400,7 +468,7 @@ pub fn divide(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
 
         (Rational(r), Mul(s1, s2)) => {
             let first = mult(
-                divide(Rational(r), *s1.clone(), ram),
+                divide(Rational(r.clone()), *s1.clone(), ram),
                 divide(Int(1), *s2.clone(), ram),
                 ram,
             );
@@ -594,16 +662,16 @@ pub fn divide(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
 
         (Div(s1, s2), Rational(r)) => {
             let first = Div(
-                Box::from(mult(*s1.clone(), Int(r.under), ram)),
-                Box::from(mult(*s2.clone(), Int(r.over), ram)),
+                Box::from(mult(*s1.clone(), from_big(r.under.clone()), ram)),
+                Box::from(mult(*s2.clone(), from_big(r.over.clone()), ram)),
             );
             first
         }
 
         (Rational(r), Div(s1, s2)) => {
             let first = Div(
-                Box::from(mult(Int(r.under), *s2.clone(), ram)),
-                Box::from(mult(*s1.clone(), Int(r.over), ram)),
+                Box::from(mult(from_big(r.under.clone()), *s2.clone(), ram)),
+                Box::from(mult(*s1.clone(), from_big(r.over.clone()), ram)),
             );
             first
         }
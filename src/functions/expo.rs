@@ -1,28 +1,120 @@
+use crate::exact_math::rationals::Rationals;
 use crate::functions::function::apply_operator;
 use crate::functions::function::apply_operator_reverse;
+use crate::functions::mult::mult;
 use crate::parsing::ast::Parameters;
 use crate::parsing::ast::Parameters::*;
+use crate::utils::complex_utils::from_complex;
+use crate::utils::integer_utils::{big_to_f64, from_big};
+use num_complex::Complex64;
+use num_traits::{One, ToPrimitive};
 
 use super::add::ORam;
 
+/// `base^exponent` on floats, a negative base raised to a fractional exponent
+/// gives the principal complex root, `(-4)^0.5` is `2i`.
+fn float_expo(base: f64, exponent: f64) -> Parameters {
+    if base < 0.0 && exponent.fract() != 0.0 {
+        from_complex(Complex64::new(base, 0.0).powf(exponent))
+    } else {
+        Float(base.powf(exponent))
+    }
+}
+
+/// Exact exponentiation of integers and rationals, `base^(p/q)` is computed as
+/// the `q`-th root of `base` raised to `p` and falls back to a float whenever
+/// the root is not exact. Negative bases get the principal complex root like
+/// floats do, so `(-8)^(1/3)` is `1+1.732i` rather than `-2`.
+fn exact_expo(base: Rationals, exponent: Rationals) -> Parameters {
+    let approx = || float_expo(base.approx(), exponent.approx());
+    let exponent = exponent.clone().reduce();
+    let negative = base.approx() < 0.0;
+    let root = match exponent.under.to_u32() {
+        Some(1) => Some(base.clone()),
+        Some(_) if negative => None,
+        Some(n) => base.root(n),
+        None => None,
+    };
+    match root.and_then(|r| r.pow(&exponent.over)) {
+        Some(r) if r.under.is_one() => from_big(r.over),
+        Some(r) => Rational(r),
+        None => approx(),
+    }
+}
+
+/// Largest integer power of a symbolic expression which is expanded into a
+/// product, `(x+1)^2` is `(x+1)*(x+1)`.
+const MAX_EXPANDED_POWER: i64 = 16;
+
+/// `base^n` for a symbolic `base`, a power for a variable `x^2` and a product
+/// of copies of the base otherwise.
+fn symbolic_expo(base: Parameters, n: i64, ram: ORam) -> Parameters {
+    match base {
+        Identifier(s) => Var(Box::from(Int(1)), n, s),
+        Var(coefficient, power, s) => Var(Box::from(expo(*coefficient, Int(n), ram)), power * n, s),
+        base if (1..=MAX_EXPANDED_POWER).contains(&n) => {
+            (1..n).fold(base.clone(), |product, _| mult(product, base.clone(), ram))
+        }
+        _ => Identifier("@Those two values are incompatible with the ^ operator".to_string()),
+    }
+}
+
 pub fn expo(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
     match (i, i2) {
         (Null, Int(v)) => Int(v),
         (Null, Float(f)) => Float(f),
         (Int(v), Null) => Int(v),
         (Float(f), Null) => Float(f),
-        (Int(v), Int(v2)) => Float((v as f64).powf(v2 as f64)),
-        (Int(v), Float(f)) => Float((v as f64).powf(f)),
-        (Float(v), Float(f)) => Float(v.powf(f)),
-        (Float(v), Int(i1)) => Float(v.powf(i1 as f64)),
+        (Int(v), Int(v2)) => exact_expo(Rationals::new(1, v), Rationals::new(1, v2)),
+        (Complex(c), Complex(c2)) => from_complex(c.powc(c2)),
+        (Complex(c), Int(i)) => from_complex(match usize::try_from(i.unsigned_abs()) {
+            Ok(n) if i < 0 => num_traits::pow(c, n).inv(),
+            Ok(n) => num_traits::pow(c, n),
+            Err(_) => c.powf(i as f64),
+        }),
+        (Int(i), Complex(c)) => from_complex(Complex64::new(i as f64, 0.0).powc(c)),
+        (Complex(c), Float(f)) => from_complex(c.powf(f)),
+        (Float(f), Complex(c)) => from_complex(Complex64::new(f, 0.0).powc(c)),
+        (Complex(c), Rational(s)) => from_complex(c.powf(s.approx())),
+        (Rational(s), Complex(c)) => from_complex(Complex64::new(s.approx(), 0.0).powc(c)),
+        (Complex(c), Big(v)) => from_complex(c.powf(big_to_f64(&v))),
+        (Big(v), Complex(c)) => from_complex(Complex64::new(big_to_f64(&v), 0.0).powc(c)),
+        (Complex(c), Null) | (Null, Complex(c)) => Complex(c),
+        (Complex(c), Identifier(s)) => match ram {
+            None => Complex(c),
+            Some(_) => apply_operator_reverse(Complex(c), Identifier(s), ram, expo),
+        },
+        (Identifier(s), Complex(c)) => match ram {
+            None => Complex(c),
+            Some(_) => apply_operator(Identifier(s), Complex(c), ram, expo),
+        },
+        (Big(v), Big(v2)) => exact_expo(Rationals::from(v), Rationals::from(v2)),
+        (Big(v), Int(i)) => exact_expo(Rationals::from(v), Rationals::new(1, i)),
+        (Int(i), Big(v)) => exact_expo(Rationals::new(1, i), Rationals::from(v)),
+        (Big(v), Rational(s)) => exact_expo(Rationals::from(v), s),
+        (Rational(s), Big(v)) => exact_expo(s, Rationals::from(v)),
+        (Rational(s), Rational(s2)) => exact_expo(s, s2),
+        (Rational(s), Int(i)) => exact_expo(s, Rationals::new(1, i)),
+        (Int(i), Rational(s)) => exact_expo(Rationals::new(1, i), s),
+        (Big(v), Null) | (Null, Big(v)) => Big(v),
+        (Big(v), Float(f)) => float_expo(big_to_f64(&v), f),
+        (Float(f), Big(v)) => float_expo(f, big_to_f64(&v)),
+        (Big(v), Identifier(s)) => match ram {
+            None => Big(v),
+            Some(_) => apply_operator_reverse(Big(v), Identifier(s), ram, expo),
+        },
+        (Identifier(s), Big(v)) => match ram {
+            None => Big(v),
+            Some(_) => apply_operator(Identifier(s), Big(v), ram, expo),
+        },
+        (Int(v), Float(f)) => float_expo(v as f64, f),
+        (Float(v), Float(f)) => float_expo(v, f),
+        (Float(v), Int(i1)) => float_expo(v, i1 as f64),
 
         (Rational(s), Null) => Rational(s.clone()),
         (Null, Rational(s)) => Rational(s.clone()),
-        (Rational(s), Rational(s2)) => Float(s.approx().powf(s2.approx())),
-        (Rational(s), Int(i)) => Float(s.approx().powf(i as f64)),
-        (Int(i), Rational(s)) => Float((i as f64).powf(s.approx())),
-        (Rational(s), Float(f)) => Float(s.approx().powf(f)),
-        (Float(f), Rational(s)) => Float(f.powf(s.approx())),
+        (Rational(s), Float(f)) => float_expo(s.approx(), f),
+        (Float(f), Rational(s)) => float_expo(f, s.approx()),
         (Bool(_), Int(i)) => Int(i),
         (Bool(_), Float(i)) => Float(i),
         (Int(i), Bool(_)) => Int(i),
@@ -35,7 +127,7 @@ pub fn expo(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
             Some(_) => apply_operator(Identifier(s), Identifier(s2), ram, expo),
         },
         (Identifier(s), Int(i)) => match ram {
-            None => Int(i),
+            None => symbolic_expo(Identifier(s), i, ram),
             Some(_) => apply_operator(Identifier(s), Int(i), ram, expo),
         },
         (Int(i), Identifier(s)) => match ram {
@@ -78,6 +170,10 @@ pub fn expo(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
             Some(_) => apply_operator(Identifier(s), Bool(b), ram, expo),
         },
 
+        (base @ (Var(..) | Plus(..) | Mul(..) | Div(..) | Call(..)), Int(i)) => {
+            symbolic_expo(base, i, ram)
+        }
+
         _ => Identifier("@Those two values are incompatible with the ^ operator".to_string()),
     }
 }
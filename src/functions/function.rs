@@ -2,6 +2,8 @@ use crate::exact_math::rationals::Rationals;
 
 use crate::parsing::ast::Parameters;
 use crate::parsing::ast::Parameters::*;
+use crate::utils::integer_utils::big_to_f64;
+use num_bigint::BigInt;
 
 use super::add::ORam;
 
@@ -87,6 +89,21 @@ pub fn greater(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
         (Bool(b), Null) => Bool(b),
         (Null, Bool(b)) => Bool(b),
         (Bool(b), Bool(b2)) => Bool(b && b2),
+        (Big(v), Big(v2)) => Bool(v > v2),
+        (Big(v), Int(i)) => Bool(v > BigInt::from(i)),
+        (Int(i), Big(v)) => Bool(BigInt::from(i) > v),
+        (Big(v), Float(f)) => Bool(big_to_f64(&v) > f),
+        (Float(f), Big(v)) => Bool(f > big_to_f64(&v)),
+        (Big(v), Rational(s)) => Bool(Rationals::from(v) > s),
+        (Rational(s), Big(v)) => Bool(s > Rationals::from(v)),
+        (Big(v), Identifier(s)) => match ram {
+            Some(_) => apply_operator_reverse(Big(v), Identifier(s), ram, greater),
+            None => Big(v),
+        },
+        (Identifier(s), Big(v)) => match ram {
+            Some(_) => apply_operator(Identifier(s), Big(v), ram, greater),
+            None => Big(v),
+        },
         (Identifier(s), Identifier(s2)) => match ram {
             None => Identifier(s),
             Some(_) => apply_operator(Identifier(s), Identifier(s2), ram, greater),
@@ -160,6 +177,21 @@ pub fn lesser(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
         (Bool(b), Null) => Bool(b),
         (Null, Bool(b)) => Bool(b),
         (Bool(b), Bool(b2)) => Bool(b && b2),
+        (Big(v), Big(v2)) => Bool(v < v2),
+        (Big(v), Int(i)) => Bool(v < BigInt::from(i)),
+        (Int(i), Big(v)) => Bool(BigInt::from(i) < v),
+        (Big(v), Float(f)) => Bool(big_to_f64(&v) < f),
+        (Float(f), Big(v)) => Bool(f < big_to_f64(&v)),
+        (Big(v), Rational(s)) => Bool(Rationals::from(v) < s),
+        (Rational(s), Big(v)) => Bool(s < Rationals::from(v)),
+        (Big(v), Identifier(s)) => match ram {
+            Some(_) => apply_operator_reverse(Big(v), Identifier(s), ram, lesser),
+            None => Big(v),
+        },
+        (Identifier(s), Big(v)) => match ram {
+            Some(_) => apply_operator(Identifier(s), Big(v), ram, lesser),
+            None => Big(v),
+        },
         (Identifier(s), Identifier(s2)) => match ram {
             None => Identifier(s),
             Some(_) => apply_operator(Identifier(s), Identifier(s2), ram, lesser),
@@ -233,6 +265,21 @@ pub fn greater_or_equal(i: Parameters, i2: Parameters, ram: ORam) -> Parameters
         (Int(i), Rational(s)) => Bool(Rationals::new(1, i) >= s),
         (Rational(s), Float(f)) => Bool(s.approx() >= f),
         (Float(f), Rational(s)) => Bool(f >= s.approx()),
+        (Big(v), Big(v2)) => Bool(v >= v2),
+        (Big(v), Int(i)) => Bool(v >= BigInt::from(i)),
+        (Int(i), Big(v)) => Bool(BigInt::from(i) >= v),
+        (Big(v), Float(f)) => Bool(big_to_f64(&v) >= f),
+        (Float(f), Big(v)) => Bool(f >= big_to_f64(&v)),
+        (Big(v), Rational(s)) => Bool(Rationals::from(v) >= s),
+        (Rational(s), Big(v)) => Bool(s >= Rationals::from(v)),
+        (Big(v), Identifier(s)) => match ram {
+            Some(_) => apply_operator_reverse(Big(v), Identifier(s), ram, greater_or_equal),
+            None => Big(v),
+        },
+        (Identifier(s), Big(v)) => match ram {
+            Some(_) => apply_operator(Identifier(s), Big(v), ram, greater_or_equal),
+            None => Big(v),
+        },
         (Identifier(s), Identifier(s2)) => match ram {
             Some(_) => apply_operator(Identifier(s), Identifier(s2), ram, greater_or_equal),
             None => Identifier(s),
@@ -308,6 +355,21 @@ pub fn lesser_or_equal(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
         (Int(i), Rational(s)) => Bool(Rationals::new(1, i) <= s),
         (Rational(s), Float(f)) => Bool(s.approx() <= f),
         (Float(f), Rational(s)) => Bool(f <= s.approx()),
+        (Big(v), Big(v2)) => Bool(v <= v2),
+        (Big(v), Int(i)) => Bool(v <= BigInt::from(i)),
+        (Int(i), Big(v)) => Bool(BigInt::from(i) <= v),
+        (Big(v), Float(f)) => Bool(big_to_f64(&v) <= f),
+        (Float(f), Big(v)) => Bool(f <= big_to_f64(&v)),
+        (Big(v), Rational(s)) => Bool(Rationals::from(v) <= s),
+        (Rational(s), Big(v)) => Bool(s <= Rationals::from(v)),
+        (Big(v), Identifier(s)) => match ram {
+            Some(_) => apply_operator_reverse(Big(v), Identifier(s), ram, lesser_or_equal),
+            None => Big(v),
+        },
+        (Identifier(s), Big(v)) => match ram {
+            Some(_) => apply_operator(Identifier(s), Big(v), ram, lesser_or_equal),
+            None => Big(v),
+        },
         (Identifier(s), Identifier(s2)) => match ram {
             None => Identifier(s),
             Some(_) => apply_operator(Identifier(s), Identifier(s2), ram, lesser_or_equal),
@@ -383,6 +445,24 @@ pub fn equal(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
         (Int(i), Rational(s)) => Bool(Rationals::new(1, i) == s),
         (Rational(s), Float(f)) => Bool(s.approx() == f),
         (Float(f), Rational(s)) => Bool(f == s.approx()),
+        (Complex(c), Complex(c2)) => Bool(c == c2),
+        (Complex(_), Int(_) | Float(_) | Rational(_) | Big(_)) => Bool(false),
+        (Int(_) | Float(_) | Rational(_) | Big(_), Complex(_)) => Bool(false),
+        (Big(v), Big(v2)) => Bool(v == v2),
+        (Big(v), Int(i)) => Bool(v == BigInt::from(i)),
+        (Int(i), Big(v)) => Bool(BigInt::from(i) == v),
+        (Big(v), Float(f)) => Bool(big_to_f64(&v) == f),
+        (Float(f), Big(v)) => Bool(f == big_to_f64(&v)),
+        (Big(v), Rational(s)) => Bool(Rationals::from(v) == s),
+        (Rational(s), Big(v)) => Bool(s == Rationals::from(v)),
+        (Big(v), Identifier(s)) => match ram {
+            Some(_) => apply_operator_reverse(Big(v), Identifier(s), ram, equal),
+            None => Big(v),
+        },
+        (Identifier(s), Big(v)) => match ram {
+            Some(_) => apply_operator(Identifier(s), Big(v), ram, equal),
+            None => Big(v),
+        },
         (Identifier(s), Identifier(s2)) => match ram {
             None => Identifier(s),
             Some(_) => apply_operator(Identifier(s), Identifier(s2), ram, equal),
@@ -488,11 +568,19 @@ pub fn or(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
 
 #[cfg(test)]
 mod test {
+    use crate::exact_math::programmer_mode::{IntType, Overflow, ProgrammerMode};
+    use crate::exact_math::rationals::Rationals;
     use crate::functions::add::add;
     use crate::functions::divide::divide;
+    use crate::functions::expo::expo;
     use crate::functions::minus::minus;
     use crate::functions::mult::mult;
+    use crate::parsing::ast::Parameters;
     use crate::parsing::ast::Parameters::*;
+    use num_bigint::BigInt;
+    use num_complex::Complex64;
+
+    use crate::PROGRAMMER_MODE;
 
     #[test]
     pub fn test_add_null() {
@@ -615,7 +703,7 @@ mod test {
 
     #[test]
     pub fn test_divide_simple() {
-        let expected = Rational(crate::exact_math::rationals::Rationals { under: 1, over: 1 });
+        let expected = Rational(crate::exact_math::rationals::Rationals::new(1, 1));
         let result = divide(Int(1), Int(1), None);
         assert_eq!(result, expected);
     }
@@ -640,4 +728,109 @@ mod test {
         let result = divide(Float(0.1), Int(2), None);
         assert_eq!(result, expected);
     }
+
+    #[test]
+    pub fn test_add_overflow() {
+        let expected = Big(BigInt::from(i64::MAX) + 1);
+        let result = add(Int(i64::MAX), Int(1), None);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    pub fn test_mult_overflow() {
+        let expected = Big(BigInt::from(i64::MAX) * 2);
+        let result = mult(Int(i64::MAX), Int(2), None);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    pub fn test_minus_big_demotes() {
+        let expected = Int(i64::MAX);
+        let result = minus(Big(BigInt::from(i64::MAX) + 1), Int(1), None);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    pub fn test_divide_big_exact() {
+        let expected = Big(BigInt::from(i64::MAX) + 1);
+        let result = divide(Big((BigInt::from(i64::MAX) + 1) * 4), Int(4), None);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    pub fn test_expo_exact() {
+        let expected = Rational(Rationals::new(9, 1));
+        let result = expo(Rational(Rationals::new(3, 1)), Int(2), None);
+        assert_eq!(result, expected);
+        assert_eq!(expo(Rational(Rationals::new(3, 1)), Int(-2), None), Int(9));
+    }
+
+    #[test]
+    pub fn test_expo_rational_root() {
+        let expected = Int(2);
+        let result = expo(Int(8), Rational(Rationals::new(3, 1)), None);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    pub fn test_expo_negative_base() {
+        let close_to = |p: Parameters, re: f64, im: f64| match p {
+            Complex(c) => (c - Complex64::new(re, im)).norm() < 1e-12,
+            _ => false,
+        };
+        assert!(close_to(expo(Float(-4.0), Float(0.5), None), 0.0, 2.0));
+        assert!(close_to(expo(Int(-4), Float(0.5), None), 0.0, 2.0));
+        let cube_root = expo(Int(-8), Rational(Rationals::new(3, 1)), None);
+        assert!(close_to(cube_root, 1.0, 3f64.sqrt()));
+        let cube_root = expo(Int(-9), Rational(Rationals::new(3, 1)), None);
+        let expected = Complex64::new(-9.0, 0.0).powf(1.0 / 3.0);
+        assert!(close_to(cube_root, expected.re, expected.im));
+        assert_eq!(expo(Int(-8), Int(2), None), Int(64));
+        assert_eq!(expo(Float(-2.0), Int(3), None), Float(-8.0));
+    }
+
+    #[test]
+    pub fn test_expo_complex_large_power() {
+        let i = Complex(Complex64::i());
+        assert_eq!(
+            expo(i.clone(), Int(1 << 40), None),
+            expo(i.clone(), Int(0), None)
+        );
+        assert_eq!(expo(i.clone(), Int((1 << 40) + 1), None), i);
+    }
+
+    #[test]
+    pub fn test_mult_complex() {
+        let expected = Complex(Complex64::new(5.0, 5.0));
+        let result = mult(
+            Complex(Complex64::new(1.0, 2.0)),
+            Complex(Complex64::new(3.0, -1.0)),
+            None,
+        );
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    pub fn test_mult_complex_demotes() {
+        let expected = Float(-1.0);
+        let result = mult(Complex(Complex64::i()), Complex(Complex64::i()), None);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    pub fn test_fixed_width_arithmetic() {
+        let u8 = IntType::parse("u8").unwrap();
+        PROGRAMMER_MODE.with(|pm| *pm.borrow_mut() = ProgrammerMode::Fixed(u8, Overflow::Wrapping));
+        assert_eq!(add(Int(255), Int(1), None), Int(0));
+        assert_eq!(minus(Null, Int(1), None), Int(255));
+        assert_eq!(mult(Int(16), Int(17), None), Int(16));
+        assert_eq!(divide(Int(7), Int(2), None), Int(3));
+        PROGRAMMER_MODE.with(|pm| *pm.borrow_mut() = ProgrammerMode::Fixed(u8, Overflow::Checked));
+        assert_eq!(
+            minus(Int(1), Int(2), None),
+            Identifier("@-1 overflows u8".to_string())
+        );
+        PROGRAMMER_MODE.with(|pm| *pm.borrow_mut() = ProgrammerMode::Off);
+        assert_eq!(add(Int(255), Int(1), None), Int(256));
+    }
 }
@@ -1,9 +1,13 @@
+use crate::exact_math::programmer_mode::fixed_width;
 use crate::exact_math::rationals::Rationals;
 use crate::exact_math::symbolic::size;
 use crate::functions::function::apply_operator;
 use crate::functions::function::apply_operator_reverse;
 use crate::parsing::ast::Parameters;
 use crate::parsing::ast::Parameters::*;
+use crate::utils::complex_utils::from_complex;
+use crate::utils::integer_utils::{big_to_f64, from_big};
+use num_bigint::BigInt;
 
 use super::add::add;
 use super::add::ORam;
@@ -11,12 +15,61 @@ use super::divide::divide;
 use super::mult::mult;
 
 pub fn minus(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
+    let fixed = match (&i, &i2) {
+        (Null, p) | (p, Null) => fixed_width(&Int(0), p, |a, b| a - b),
+        (p, p2) => fixed_width(p, p2, |a, b| a - b),
+    };
+    if let Some(r) = fixed {
+        return r;
+    }
     match (i, i2) {
-        (Null, Int(v)) => Int(-v),
+        (Null, Int(v)) | (Int(v), Null) => match v.checked_neg() {
+            Some(r) => Int(r),
+            None => from_big(-BigInt::from(v)),
+        },
         (Null, Float(f)) => Float(-f),
-        (Int(v), Null) => Int(-v),
         (Float(f), Null) => Float(-f),
-        (Int(v), Int(v2)) => Int(v - v2),
+        (Int(v), Int(v2)) => match v.checked_sub(v2) {
+            Some(r) => Int(r),
+            None => from_big(BigInt::from(v) - v2),
+        },
+        (Complex(c), Complex(c2)) => from_complex(c - c2),
+        (Complex(c), Int(i)) => from_complex(c - i as f64),
+        (Int(i), Complex(c)) => from_complex(i as f64 - c),
+        (Complex(c), Float(f)) => from_complex(c - f),
+        (Float(f), Complex(c)) => from_complex(f - c),
+        (Complex(c), Rational(s)) => from_complex(c - s.approx()),
+        (Rational(s), Complex(c)) => from_complex(s.approx() - c),
+        (Complex(c), Big(v)) => from_complex(c - big_to_f64(&v)),
+        (Big(v), Complex(c)) => from_complex(big_to_f64(&v) - c),
+        (Complex(c), Null) | (Null, Complex(c)) => Complex(-c),
+        (Complex(c), Identifier(s)) => match ram {
+            None => Plus(
+                Box::from(Complex(c)),
+                Box::from(Var(Box::from(Int(-1)), 1, s)),
+            ),
+            Some(_) => apply_operator_reverse(Complex(c), Identifier(s), ram, minus),
+        },
+        (Identifier(s), Complex(c)) => match ram {
+            None => Plus(Box::from(Identifier(s)), Box::from(Complex(-c))),
+            Some(_) => apply_operator(Identifier(s), Complex(c), ram, minus),
+        },
+        (Big(v), Big(v2)) => from_big(v - v2),
+        (Big(v), Int(i)) => from_big(v - i),
+        (Int(i), Big(v)) => from_big(BigInt::from(i) - v),
+        (Big(v), Null) | (Null, Big(v)) => from_big(-v),
+        (Big(v), Float(f)) => Float(big_to_f64(&v) - f),
+        (Float(f), Big(v)) => Float(f - big_to_f64(&v)),
+        (Big(v), Rational(s)) => Rational(Rationals::from(v) - s),
+        (Rational(s), Big(v)) => Rational(s - Rationals::from(v)),
+        (Big(v), Identifier(s)) => match ram {
+            None => Plus(Box::from(Big(v)), Box::from(Var(Box::from(Int(-1)), 1, s))),
+            Some(_) => apply_operator_reverse(Big(v), Identifier(s), ram, minus),
+        },
+        (Identifier(s), Big(v)) => match ram {
+            None => Plus(Box::from(Identifier(s)), Box::from(Big(-v))),
+            Some(_) => apply_operator(Identifier(s), Big(v), ram, minus),
+        },
 
         (Rational(s), Null) => Rational(Rationals::new(1, 0) - s),
 
@@ -84,7 +137,7 @@ pub fn minus(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
         },
         (Identifier(s), Null) => match ram {
             None => Var(Box::from(Int(-1)), 1, s),
-            Some(_) => apply_operator_reverse(Identifier(s), Null, ram, minus),
+            Some(_) => apply_operator(Identifier(s), Null, ram, minus),
         },
         (Rational(r), Identifier(ss)) => match ram {
             None => Plus(
@@ -262,7 +315,10 @@ pub fn minus(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
         }
 
         (Plus(s1, s2), Rational(r)) => {
-            let first = Plus(Box::from(minus(*s1.clone(), Rational(r), ram)), s2.clone());
+            let first = Plus(
+                Box::from(minus(*s1.clone(), Rational(r.clone()), ram)),
+                s2.clone(),
+            );
             let second = Plus(s1.clone(), Box::from(minus(*s2.clone(), Rational(r), ram)));
 
             let (s1, s2) = (size(&first), size(&second));
@@ -275,7 +331,7 @@ pub fn minus(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
 
         (Rational(r), Plus(s1, s2)) => {
             let first = Plus(
-                Box::from(minus(Rational(r), *s1.clone(), ram)),
+                Box::from(minus(Rational(r.clone()), *s1.clone(), ram)),
                 Box::from(minus(Int(0), *s2.clone(), ram)),
             );
             let second = Plus(
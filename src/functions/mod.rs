@@ -1,6 +1,8 @@
 pub mod add;
+pub mod bitwise;
 pub mod divide;
 pub mod expo;
 pub mod function;
 pub mod minus;
+pub mod modulo;
 pub mod mult;
@@ -0,0 +1,161 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+use crate::exact_math::rationals::Rationals;
+use crate::parsing::ast::Parameters;
+use crate::parsing::ast::Parameters::*;
+use crate::utils::integer_utils::from_big;
+
+use super::add::ORam;
+use super::function::{apply_operator, apply_operator_reverse};
+
+/// Operands of `mod` and `//`, integers and rationals are kept exact.
+enum Operand {
+    Exact(Rationals),
+    Approx(f64),
+}
+
+fn to_operand(p: &Parameters) -> Option<Operand> {
+    match p {
+        Int(i) => Some(Operand::Exact(Rationals::from(BigInt::from(*i)))),
+        Big(b) => Some(Operand::Exact(Rationals::from(b.clone()))),
+        Rational(r) => Some(Operand::Exact(r.clone())),
+        Float(f) => Some(Operand::Approx(*f)),
+        _ => None,
+    }
+}
+
+fn approx(o: &Operand) -> f64 {
+    match o {
+        Operand::Exact(r) => r.approx(),
+        Operand::Approx(f) => *f,
+    }
+}
+
+fn from_rational(r: Rationals) -> Parameters {
+    let r = r.reduce();
+    if r.under.is_one() {
+        from_big(r.over)
+    } else {
+        Rational(r)
+    }
+}
+
+/// `floor(a / b)` for two exact operands, `b` must not be zero.
+fn floor_quotient(a: &Rationals, b: &Rationals) -> BigInt {
+    (&a.over * &b.under).div_floor(&(&a.under * &b.over))
+}
+
+fn is_zero(o: &Operand) -> bool {
+    match o {
+        Operand::Exact(r) => r.over.is_zero(),
+        Operand::Approx(f) => *f == 0.0,
+    }
+}
+
+fn integer_division(
+    i: Parameters,
+    i2: Parameters,
+    ram: ORam,
+    this: fn(Parameters, Parameters, ORam) -> Parameters,
+    exact: fn(Rationals, Rationals) -> Parameters,
+    float: fn(f64, f64) -> f64,
+) -> Parameters {
+    match (i, i2) {
+        (Null, p) | (p, Null) => p,
+        (Identifier(s), p) => match ram {
+            Some(_) => apply_operator(Identifier(s), p, ram, this),
+            None => Identifier(s),
+        },
+        (p, Identifier(s)) => match ram {
+            Some(_) => apply_operator_reverse(p, Identifier(s), ram, this),
+            None => Identifier(s),
+        },
+        (p, p2) => match (to_operand(&p), to_operand(&p2)) {
+            (_, Some(b)) if is_zero(&b) => Identifier("@division by zero".to_string()),
+            (Some(Operand::Exact(a)), Some(Operand::Exact(b))) => exact(a, b),
+            (Some(a), Some(b)) => Float(float(approx(&a), approx(&b))),
+            _ => Identifier("@mod and // only apply to real numbers".to_string()),
+        },
+    }
+}
+
+/// Remainder of the floored division, it has the sign of the divisor.
+pub fn modulo(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
+    integer_division(
+        i,
+        i2,
+        ram,
+        modulo,
+        |a, b| {
+            let q = Rationals::from(floor_quotient(&a, &b));
+            from_rational(a - b * q)
+        },
+        |a, b| a - b * (a / b).floor(),
+    )
+}
+
+pub fn floor_divide(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
+    integer_division(
+        i,
+        i2,
+        ram,
+        floor_divide,
+        |a, b| from_big(floor_quotient(&a, &b)),
+        |a, b| (a / b).floor(),
+    )
+}
+
+/// `[a // b, a mod b]`
+pub fn divmod(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
+    let quotient = floor_divide(i.clone(), i2.clone(), ram);
+    if quotient.is_error() {
+        return quotient;
+    }
+    InterpreterVector(Box::from(vec![quotient, modulo(i, i2, ram)]))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::exact_math::rationals::Rationals;
+    use crate::parsing::ast::Parameters::*;
+
+    use super::{divmod, floor_divide, modulo};
+
+    #[test]
+    pub fn test_modulo_negative() {
+        assert_eq!(modulo(Int(7), Int(3), None), Int(1));
+        assert_eq!(modulo(Int(-7), Int(3), None), Int(2));
+        assert_eq!(modulo(Int(7), Int(-3), None), Int(-2));
+        assert_eq!(modulo(Float(-7.5), Int(2), None), Float(0.5));
+    }
+
+    #[test]
+    pub fn test_floor_divide() {
+        assert_eq!(floor_divide(Int(7), Int(2), None), Int(3));
+        assert_eq!(floor_divide(Int(-7), Int(2), None), Int(-4));
+        assert_eq!(floor_divide(Float(-7.5), Int(2), None), Float(-4.0));
+        assert_eq!(
+            floor_divide(Int(1), Int(0), None),
+            Identifier("@division by zero".to_string())
+        );
+    }
+
+    #[test]
+    pub fn test_modulo_rational() {
+        let expected = Rational(Rationals::new(6, 1));
+        let result = modulo(
+            Rational(Rationals::new(2, 7)),
+            Rational(Rationals::new(3, 1)),
+            None,
+        );
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    pub fn test_divmod() {
+        let expected = InterpreterVector(Box::from(vec![Int(-3), Int(2)]));
+        assert_eq!(divmod(Int(-7), Int(3), None), expected);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::exact_math::programmer_mode::fixed_width;
 use crate::exact_math::rationals::Rationals;
 use crate::exact_math::symbolic::size;
 use crate::functions::add::add;
@@ -5,17 +6,52 @@ use crate::functions::function::apply_operator;
 use crate::functions::function::apply_operator_reverse;
 use crate::parsing::ast::Parameters;
 use crate::parsing::ast::Parameters::*;
+use crate::utils::complex_utils::from_complex;
+use crate::utils::integer_utils::{big_to_f64, from_big};
 use crate::utils::matrix_utils::*;
+use num_bigint::BigInt;
 
 use super::add::ORam;
 
 pub fn mult(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
+    if let Some(r) = fixed_width(&i, &i2, |a, b| a * b) {
+        return r;
+    }
     match (i, i2) {
         (Null, Int(v)) => Int(v),
         (Null, Float(f)) => Float(f),
         (Int(v), Null) => Int(v),
         (Float(f), Null) => Float(f),
-        (Int(v), Int(v2)) => Int(v * v2),
+        (Int(v), Int(v2)) => match v.checked_mul(v2) {
+            Some(r) => Int(r),
+            None => from_big(BigInt::from(v) * v2),
+        },
+        (Complex(c), Complex(c2)) => from_complex(c * c2),
+        (Complex(c), Int(i)) | (Int(i), Complex(c)) => from_complex(c * i as f64),
+        (Complex(c), Float(f)) | (Float(f), Complex(c)) => from_complex(c * f),
+        (Complex(c), Rational(s)) | (Rational(s), Complex(c)) => from_complex(c * s.approx()),
+        (Complex(c), Big(v)) | (Big(v), Complex(c)) => from_complex(c * big_to_f64(&v)),
+        (Complex(c), Null) | (Null, Complex(c)) => Complex(c),
+        (Complex(c), Identifier(s)) | (Identifier(s), Complex(c)) => match ram {
+            None => Var(Box::from(Complex(c)), 1, s),
+            Some(_) => apply_operator(Identifier(s), Complex(c), ram, mult),
+        },
+        (Big(v), Big(v2)) => from_big(v * v2),
+        (Big(v), Int(i)) | (Int(i), Big(v)) => from_big(v * i),
+        (Big(v), Float(f)) | (Float(f), Big(v)) => Float(big_to_f64(&v) * f),
+        (Big(v), Rational(s)) | (Rational(s), Big(v)) => Rational(s * Rationals::from(v)),
+        (Big(v), Null) | (Null, Big(v)) => Big(v),
+        (Big(v), InterpreterVector(vec)) | (InterpreterVector(vec), Big(v)) => {
+            let mut result = Vec::new();
+            vec.into_iter()
+                .map(|x| mult(x.clone(), Big(v.clone()), ram))
+                .for_each(|x| result.push(x));
+            InterpreterVector(Box::from(result))
+        }
+        (Big(v), Identifier(s)) | (Identifier(s), Big(v)) => match ram {
+            None => Var(Box::from(Big(v)), 1, s),
+            Some(_) => apply_operator(Identifier(s), Big(v), ram, mult),
+        },
         (Int(v), Float(f)) => Float((v as f64) * f),
         (Float(v), Float(f)) => Float(v * f),
         (Float(v), Int(i1)) => Float(v * (i1 as f64)),
@@ -269,7 +305,7 @@ pub fn mult(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
         }
         (Rational(r), Plus(s1, s2)) => {
             let first = Plus(
-                Box::from(mult(Rational(r), *s1.clone(), ram)),
+                Box::from(mult(Rational(r.clone()), *s1.clone(), ram)),
                 Box::from(mult(Rational(r), *s2.clone(), ram)),
             );
             first
@@ -277,7 +313,7 @@ pub fn mult(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
 
         (Plus(s1, s2), Rational(r)) => {
             let first = Plus(
-                Box::from(mult(*s1.clone(), Rational(r), ram)),
+                Box::from(mult(*s1.clone(), Rational(r.clone()), ram)),
                 Box::from(mult(*s2.clone(), Rational(r), ram)),
             );
             first
@@ -528,16 +564,16 @@ pub fn mult(i: Parameters, i2: Parameters, ram: ORam) -> Parameters {
 
         (Div(s1, s2), Rational(r)) => {
             let first = Div(
-                Box::from(mult(*s1.clone(), Int(r.over), ram)),
-                Box::from(mult(*s2.clone(), Int(r.under), ram)),
+                Box::from(mult(*s1.clone(), from_big(r.over.clone()), ram)),
+                Box::from(mult(*s2.clone(), from_big(r.under.clone()), ram)),
             );
             first
         }
 
         (Rational(r), Div(s1, s2)) => {
             let first = Div(
-                Box::from(mult(Int(r.over), *s1.clone(), ram)),
-                Box::from(mult(*s2.clone(), Int(r.under), ram)),
+                Box::from(mult(from_big(r.over.clone()), *s1.clone(), ram)),
+                Box::from(mult(*s2.clone(), from_big(r.under.clone()), ram)),
             );
             first
         }
@@ -1,16 +1,29 @@
+use std::collections::HashSet;
+
+use num_complex::Complex64;
+
 use crate::exact_math::float_mode::FloatMode;
+use crate::exact_math::programmer_mode::fit_parameter;
 use crate::exact_math::rationals::Rationals;
 use crate::functions::add::add;
+use crate::functions::bitwise::*;
 use crate::functions::divide::divide;
 use crate::functions::expo::expo;
 use crate::functions::function::*;
 use crate::functions::minus::minus;
+use crate::functions::modulo::{floor_divide, modulo};
 use crate::functions::mult::mult;
-use crate::interpreting::stdlib::exec;
-use crate::parsing::ast::{Ast, Functions, Parameters, Ram};
+use crate::interpreting::memo;
+use crate::interpreting::scope::Scope;
+use crate::interpreting::stdlib::{exec, exec_named, is_builtin};
+use crate::parsing::ast::{Ast, Equation, Functions, Lambda, Parameters, Ram};
 use crate::FLOAT_MODE;
 
-pub fn interpret(ast: &Ast, mut ram: &mut Ram, mut function: &mut Functions) -> Parameters {
+pub fn interpret(ast: &Ast, ram: &mut Ram, function: &mut Functions) -> Parameters {
+    evaluate(ast, &mut Scope::new(ram, function))
+}
+
+pub fn evaluate(ast: &Ast, scope: &mut Scope) -> Parameters {
     match ast {
         Ast::Nil => Parameters::Null,
         Ast::Node {
@@ -18,140 +31,602 @@ pub fn interpret(ast: &Ast, mut ram: &mut Ram, mut function: &mut Functions) ->
             left: l,
             right: r,
         } => {
-            let param1 = interpret(l, &mut ram, &mut function);
-            let param2 = interpret(r, &mut ram, &mut function);
+            // the target of an assignment is not evaluated, it may call the
+            // function being redefined
+            let param1 = match v {
+                Parameters::Assign | Parameters::CompoundAssign(_) | Parameters::When => {
+                    Parameters::Null
+                }
+                _ => evaluate(l, scope),
+            };
+            // the body of a function is only evaluated when it is called, a
+            // compound assignment evaluates its operation once the target is
+            // known to be bound
+            let param2 = match (v, &**l) {
+                (Parameters::When | Parameters::CompoundAssign(_), _) => Parameters::Null,
+                (Parameters::Assign, Ast::Call { .. }) => Parameters::Null,
+                (
+                    Parameters::Assign,
+                    Ast::Node {
+                        value: Parameters::When,
+                        ..
+                    },
+                ) => Parameters::Null,
+                _ => evaluate(r, scope),
+            };
+            if param1.is_error() {
+                return param1;
+            }
+            if param2.is_error() {
+                return param2;
+            }
             let last = match v {
-                Parameters::PlusOperation => add(param1, param2, Some(&ram)),
-                Parameters::MinusOperation => minus(param1, param2, Some(&ram)),
-                Parameters::MultiplicationOperation => mult(param1, param2, Some(&ram)),
-                Parameters::DivideOperation => divide(param1, param2, Some(&ram)),
-                Parameters::ExpoOperation => expo(param1, param2, Some(&ram)),
-                Parameters::Equal => equal(param1, param2, Some(&ram)),
-                Parameters::Not => not(param1, param2, Some(&ram)),
-                Parameters::GreaterOperation => greater(param1, param2, Some(&ram)),
-                Parameters::GreaterOrEqualOperation => greater_or_equal(param1, param2, Some(&ram)),
-                Parameters::LesserOperation => lesser(param1, param2, Some(&ram)),
-                Parameters::LesserOrEqualOperation => lesser_or_equal(param1, param2, Some(&ram)),
-                Parameters::AndOperation => and(param1, param2, Some(&ram)),
-                Parameters::OrOperation => or(param1, param2, Some(&ram)),
+                Parameters::PlusOperation => add(param1, param2, Some(scope.ram)),
+                Parameters::MinusOperation => minus(param1, param2, Some(scope.ram)),
+                Parameters::MultiplicationOperation => mult(param1, param2, Some(scope.ram)),
+                Parameters::DivideOperation => divide(param1, param2, Some(scope.ram)),
+                Parameters::ModOperation => modulo(param1, param2, Some(scope.ram)),
+                Parameters::FloorDivideOperation => floor_divide(param1, param2, Some(scope.ram)),
+                Parameters::ExpoOperation => expo(param1, param2, Some(scope.ram)),
+                Parameters::Equal => equal(param1, param2, Some(scope.ram)),
+                Parameters::NotEqual => not(
+                    equal(param1, param2, Some(scope.ram)),
+                    Parameters::Null,
+                    None,
+                ),
+                Parameters::Not => not(param1, param2, Some(scope.ram)),
+                Parameters::GreaterOperation => greater(param1, param2, Some(scope.ram)),
+                Parameters::GreaterOrEqualOperation => {
+                    greater_or_equal(param1, param2, Some(scope.ram))
+                }
+                Parameters::LesserOperation => lesser(param1, param2, Some(scope.ram)),
+                Parameters::LesserOrEqualOperation => {
+                    lesser_or_equal(param1, param2, Some(scope.ram))
+                }
+                Parameters::AndOperation => and(param1, param2, Some(scope.ram)),
+                Parameters::OrOperation => or(param1, param2, Some(scope.ram)),
+                Parameters::BitAndOperation => bit_and(param1, param2, Some(scope.ram)),
+                Parameters::BitOrOperation => bit_or(param1, param2, Some(scope.ram)),
+                Parameters::XorOperation => xor(param1, param2, Some(scope.ram)),
+                Parameters::BitNotOperation => bit_not(param1, param2, Some(scope.ram)),
+                Parameters::ShiftLeftOperation => shift_left(param1, param2, Some(scope.ram)),
+                Parameters::ShiftRightOperation => shift_right(param1, param2, Some(scope.ram)),
+                Parameters::Delete(name) => delete(name, scope),
+                Parameters::Memo(name) => memoize(name, scope),
+                Parameters::When => Parameters::Identifier(
+                    "@when can only guard the definition of a function".to_string(),
+                ),
+                Parameters::Variadic(name) => Parameters::Identifier(format!(
+                    "@...{name} can only be the last parameter of a function"
+                )),
+                Parameters::Lambda(lambda) => Parameters::Lambda(Box::from(capture(lambda, scope))),
                 Parameters::Rational(s) => Parameters::Rational(s.clone()),
                 Parameters::Str(s) => Parameters::Str(s.to_string()),
                 Parameters::Assign => match *(l.clone()) {
                     Ast::Call { name: n, lst: list } => {
-                        if function.contains_key(&n) {
-                            println!(
-                                "{}",
-                                ansi_term::Color::Red
-                                    .bold()
-                                    .paint("This function has already been set")
-                            );
-                            Parameters::Null
-                        } else {
-                            if n.as_str() != "" {
-                                (function).insert(n.to_string(), (list.clone(), *r.clone()));
-                            }
-                            println!(
-                                "{}: {} = {}",
-                                ansi_term::Color::Cyan.paint("fun"),
-                                ansi_term::Color::RGB(255, 215, 0).paint(format!(
-                                    "{}",
-                                    Ast::Call {
-                                        name: n.clone(),
-                                        lst: list.clone()
-                                    }
-                                )),
-                                ansi_term::Color::RGB(255, 215, 0).paint(format!("{}", *r.clone()))
-                            );
-                            Parameters::Null
-                        }
+                        define(&n, Equation::new(list, *r.clone()), scope)
                     }
-                    _ => {
-                        let p1 = match *l.clone() {
-                            Ast::Node { value, left, right } => {
-                                match (value.clone(), *left, *right) {
-                                    (Parameters::Identifier(_), Ast::Nil, Ast::Nil) => {
-                                        value.clone()
-                                    }
-                                    _ => Parameters::Null,
-                                }
-                            }
-                            _ => Parameters::Null,
-                        };
-
-                        let (a, b) = assign(p1, param2.clone());
-                        if a != "".to_string() {
-                            if ram.contains_key(&a) {
-                                ram.remove(&a);
-                            }
-                            (ram).insert(a.clone(), b.clone());
-
-                            println!(
-                                "{}: {} = {}",
-                                ansi_term::Color::Cyan.paint("assign"),
-                                ansi_term::Color::Yellow.paint(format!("{}", a.clone())),
-                                ansi_term::Color::Yellow.paint(format!(
-                                    "{}",
-                                    b.clone().pretty_print(Some(ram), Some(function))
-                                ))
-                            );
-
-                            return Parameters::Null;
+                    Ast::Node {
+                        value: Parameters::When,
+                        left: call,
+                        right: guard,
+                    } => match *call {
+                        Ast::Call { name: n, lst: list } => {
+                            let equation = Equation {
+                                params: list,
+                                guard: *guard,
+                                body: *r.clone(),
+                            };
+                            define(&n, equation, scope)
                         }
-                        Parameters::Null
-                    }
+                        _ => Parameters::Identifier("@invalid assignment target".to_string()),
+                    },
+                    target => assign_to(&target, param2, scope),
                 },
+                Parameters::CompoundAssign(op) => update(l, op, r, scope),
                 Parameters::Float(f) => FLOAT_MODE.with(|fm| match *fm.borrow() {
-                    FloatMode::Exact => Parameters::Rational(Rationals::rationalize(*f)),
+                    FloatMode::Exact if f.is_finite() => {
+                        Parameters::Rational(Rationals::rationalize(*f))
+                    }
                     _ => Parameters::Float(*f),
                 }),
                 Parameters::Int(i) => Parameters::Int(*i),
-                Parameters::Identifier(s) => {
-                    if ram.contains_key(s) {
-                        ram.get(s).unwrap().clone()
-                    } else {
-                        Parameters::Identifier(s.clone())
-                    }
-                }
+                Parameters::Big(i) => Parameters::Big(i.clone()),
+                Parameters::Complex(c) => Parameters::Complex(*c),
+                Parameters::Identifier(s) => match scope.get(s) {
+                    Some(value) => value.clone(),
+                    // the imaginary unit unless a variable shadows it
+                    None if s == "i" => Parameters::Complex(Complex64::i()),
+                    None => Parameters::Identifier(s.clone()),
+                },
                 Parameters::Bool(b) => Parameters::Bool(*b),
                 Parameters::Null => Parameters::Null,
                 Parameters::Vector(a) => {
                     let mut vec = Vec::new();
                     (*a).clone()
                         .into_iter()
-                        .map(|a| interpret(&a, ram, function))
+                        .map(|a| evaluate(&a, scope))
                         .for_each(|s| vec.push(s));
                     Parameters::InterpreterVector(Box::from(vec))
                 }
                 Parameters::InterpreterVector(a) => Parameters::InterpreterVector(a.clone()),
                 Parameters::Var(x, y, z) => Parameters::Var(x.clone(), *y, z.clone()),
-                Parameters::Plus(x, y) => add(*x.clone(), *y.clone(), Some(&ram)),
-                Parameters::Mul(x, y) => mult(*x.clone(), *y.clone(), Some(&ram)),
-                Parameters::Div(x, y) => divide(*x.clone(), *y.clone(), Some(&ram)),
-                Parameters::Call(x, y) => {
-                    exec(x.clone(), vec![*y.clone()], Some(ram), Some(function))
-                }
+                Parameters::Plus(x, y) => add(*x.clone(), *y.clone(), Some(scope.ram)),
+                Parameters::Mul(x, y) => mult(*x.clone(), *y.clone(), Some(scope.ram)),
+                Parameters::Div(x, y) => divide(*x.clone(), *y.clone(), Some(scope.ram)),
+                Parameters::Call(x, y) => exec(x.clone(), vec![*y.clone()], scope),
             };
-            last.clone()
+            fit_parameter(last)
         }
         Ast::Call { name: n, lst: list } => {
-            let v: Vec<Parameters> = list.iter().map(|x| interpret(x, ram, function)).collect();
-            exec(n.to_string(), v, Some(&mut ram), Some(&mut function))
+            // `f(x=3)` passes the argument named x instead of assigning
+            let mut v = Vec::new();
+            let mut named = Vec::new();
+            for x in list {
+                match (x, x.parameter_name()) {
+                    (
+                        Ast::Node {
+                            value: Parameters::Assign,
+                            right,
+                            ..
+                        },
+                        Some(arg),
+                    ) => named.push((arg.to_string(), evaluate(right, scope))),
+                    _ => v.push(evaluate(x, scope)),
+                }
+            }
+            let named_values = named.iter().map(|(_, x)| x);
+            if let Some(error) = v.iter().chain(named_values).find(|x| x.is_error()) {
+                return error.clone();
+            }
+            fit_parameter(exec_named(n.to_string(), v, named, scope))
         }
+        Ast::If {
+            condition,
+            then,
+            otherwise,
+        } => match evaluate(condition, scope) {
+            Parameters::Bool(true) => evaluate(then, scope),
+            Parameters::Bool(false) => evaluate(otherwise, scope),
+            error if error.is_error() => error,
+            _ => Parameters::Identifier("@the condition of an if must be a boolean".to_string()),
+        },
+        Ast::Block(statements) => local(scope, |scope| {
+            let mut last = Parameters::Null;
+            for statement in statements {
+                last = evaluate(statement, scope);
+                if last.is_error() {
+                    break;
+                }
+            }
+            last
+        }),
+        Ast::For {
+            variable,
+            iterable,
+            body,
+        } => {
+            let values = match evaluate(iterable, scope) {
+                Parameters::InterpreterVector(values) => values,
+                error if error.is_error() => return error,
+                p => {
+                    return Parameters::Identifier(format!(
+                        "@only vectors can be iterated, not {p}"
+                    ))
+                }
+            };
+            // the loop variable is local to the loop as well
+            let previous = scope.ram.remove(variable);
+            let mut last = Parameters::Null;
+            for value in *values {
+                scope.ram.insert(variable.clone(), value);
+                globals_changed(scope, std::slice::from_ref(variable));
+                last = evaluate(body, scope);
+                if last.is_error() {
+                    break;
+                }
+            }
+            scope.ram.remove(variable);
+            if let Some(value) = previous {
+                scope.ram.insert(variable.clone(), value);
+            }
+            globals_changed(scope, std::slice::from_ref(variable));
+            match last {
+                error if error.is_error() => error,
+                _ => Parameters::Null,
+            }
+        }
+        Ast::While { condition, body } => loop {
+            match evaluate(condition, scope) {
+                Parameters::Bool(true) => {
+                    let last = evaluate(body, scope);
+                    if last.is_error() {
+                        return last;
+                    }
+                }
+                Parameters::Bool(false) => return Parameters::Null,
+                error if error.is_error() => return error,
+                _ => {
+                    return Parameters::Identifier(
+                        "@the condition of a while must be a boolean".to_string(),
+                    )
+                }
+            }
+        },
     }
 }
 
+/// Adds an equation to the function `name`, it replaces the equation covering
+/// the same case if there is one.
+fn define(name: &str, equation: Equation, scope: &mut Scope) -> Parameters {
+    if name.is_empty() {
+        return Parameters::Null;
+    }
+    if is_builtin(name) {
+        return builtin_redefined(name);
+    }
+    let last = equation.params.len().saturating_sub(1);
+    for (i, param) in equation.params.iter().enumerate() {
+        if let Ast::Node {
+            value: Parameters::Variadic(args),
+            ..
+        } = param
+        {
+            if i != last {
+                return Parameters::Identifier(format!(
+                    "@...{args} can only be the last parameter of a function"
+                ));
+            }
+        }
+    }
+    let head = equation.head(name);
+    let body = equation.body.to_string();
+    globals_changed(scope, &[name.to_string()]);
+    let equations = scope.functions.entry(name.to_string()).or_default();
+    match equations.iter().position(|e| e.same_case(&equation)) {
+        Some(i) => equations[i] = equation,
+        None => equations.push(equation),
+    }
+    if scope.echo {
+        println!(
+            "{}: {} = {}",
+            ansi_term::Color::Cyan.paint("fun"),
+            ansi_term::Color::RGB(255, 215, 0).paint(head),
+            ansi_term::Color::RGB(255, 215, 0).paint(body)
+        );
+    }
+    Parameters::Null
+}
+
+fn builtin_redefined(name: &str) -> Parameters {
+    Parameters::Identifier(format!(
+        "@{name} is a built-in function and cannot be redefined"
+    ))
+}
+
+/// Binds the variables of `target` to `value`, nothing is bound unless the
+/// whole target matches.
+fn assign_to(target: &Ast, value: Parameters, scope: &mut Scope) -> Parameters {
+    let mut bindings = Vec::new();
+    if let Err(error) = destructure(target, value, &mut bindings) {
+        return error;
+    }
+    // `[a, b] = [b, a]` or `x = x + 1` on unbound names would make a
+    // variable its own value, which never ends being looked up
+    let mut unknowns = Vec::new();
+    bindings
+        .iter()
+        .for_each(|(_, value)| unknown_names(value, &mut unknowns));
+    if let Some((name, _)) = bindings.iter().find(|(name, _)| unknowns.contains(name)) {
+        return Parameters::Identifier(format!("@{name} cannot be defined in terms of itself"));
+    }
+    // a lambda bound to the name of a built-in could never be called
+    let shadowing = bindings
+        .iter()
+        .find(|(name, value)| matches!(value, Parameters::Lambda(_)) && is_builtin(name));
+    if let Some((name, _)) = shadowing {
+        return builtin_redefined(name);
+    }
+    let names: Vec<String> = bindings.iter().map(|(name, _)| name.clone()).collect();
+    globals_changed(scope, &names);
+    for (name, value) in bindings {
+        scope.ram.insert(name.clone(), value.clone());
+        if scope.echo {
+            println!(
+                "{}: {} = {}",
+                ansi_term::Color::Cyan.paint("assign"),
+                ansi_term::Color::Yellow.paint(name),
+                ansi_term::Color::Yellow
+                    .paint(value.pretty_print(Some(scope.ram), Some(scope.functions)))
+            );
+        }
+    }
+    Parameters::Null
+}
+
+/// Applies `op` to the variables of `target` and `operand`, `x += 1`. Unlike
+/// `x = x + 1` it fails when a variable is not bound, instead of binding it
+/// to an expression referring to itself.
+fn update(target: &Ast, op: &Parameters, operand: &Ast, scope: &mut Scope) -> Parameters {
+    let mut names = Vec::new();
+    referenced_names(target, &mut names);
+    if let Some(name) = names.iter().find(|name| scope.get(name).is_none()) {
+        return Parameters::Identifier(format!("@undefined variable {name}"));
+    }
+    let operation = Ast::Node {
+        value: op.clone(),
+        left: Box::from(target.clone()),
+        right: Box::from(operand.clone()),
+    };
+    match evaluate(&operation, scope) {
+        error if error.is_error() => error,
+        value => assign_to(target, value, scope),
+    }
+}
+
+/// Pairs the variables of a target, a variable or a vector of targets
+/// `[a, [b, c]]`, with the matching parts of `value`.
+fn destructure(
+    target: &Ast,
+    value: Parameters,
+    bindings: &mut Vec<(String, Parameters)>,
+) -> Result<(), Parameters> {
+    match target {
+        Ast::Node {
+            value: name @ Parameters::Identifier(_),
+            ..
+        } => {
+            let (name, value) = assign(name.clone(), value);
+            if !name.is_empty() {
+                bindings.push((name, value));
+            }
+            Ok(())
+        }
+        Ast::Node {
+            value: Parameters::Vector(targets),
+            ..
+        } => match value {
+            Parameters::InterpreterVector(values) if values.len() == targets.len() => targets
+                .iter()
+                .zip(*values)
+                .try_for_each(|(target, value)| destructure(target, value, bindings)),
+            Parameters::InterpreterVector(values) => Err(Parameters::Identifier(format!(
+                "@cannot destructure a vector of length {} into {} targets",
+                values.len(),
+                targets.len()
+            ))),
+            p => Err(Parameters::Identifier(format!(
+                "@only vectors can be destructured, not {p}"
+            ))),
+        },
+        _ => Err(Parameters::Identifier(
+            "@invalid assignment target".to_string(),
+        )),
+    }
+}
+
+/// Runs `f` in the current scope without echoing, then forgets the variables
+/// and functions it defined so they do not leak out of the block. Assigning a
+/// variable which already existed updates it.
+fn local(scope: &mut Scope, f: impl FnOnce(&mut Scope) -> Parameters) -> Parameters {
+    let variables: HashSet<String> = scope.ram.keys().cloned().collect();
+    let functions: HashSet<String> = scope.functions.keys().cloned().collect();
+    let echo = std::mem::replace(&mut scope.echo, false);
+    let result = f(scope);
+    scope.echo = echo;
+    let removed: Vec<String> = scope
+        .ram
+        .keys()
+        .filter(|name| !variables.contains(*name))
+        .chain(
+            scope
+                .functions
+                .keys()
+                .filter(|name| !functions.contains(*name)),
+        )
+        .cloned()
+        .collect();
+    scope.ram.retain(|name, _| variables.contains(name));
+    scope.functions.retain(|name, _| functions.contains(name));
+    if !removed.is_empty() {
+        globals_changed(scope, &removed);
+    }
+    result
+}
+
+/// Memoized functions may read global variables and call other functions,
+/// so the caches of those depending on one of the `changed` names are
+/// emptied. The loop variable of a `for` only matters to the functions using
+/// it as a global.
+fn globals_changed(scope: &Scope, changed: &[String]) {
+    if scope.is_root() {
+        memo::clear_where(|name| {
+            let used = global_names(name, scope);
+            changed.iter().any(|name| used.contains(name))
+        });
+    }
+}
+
+/// The global names the function `name` depends on, itself included, through
+/// the functions and the lambdas it calls. Parameters shadow globals.
+fn global_names(name: &str, scope: &Scope) -> HashSet<String> {
+    let mut used = HashSet::from([name.to_string()]);
+    let mut pending = vec![name.to_string()];
+    while let Some(name) = pending.pop() {
+        let mut names = Vec::new();
+        for equation in scope.functions.get(&name).into_iter().flatten() {
+            let mut params = Vec::new();
+            for param in &equation.params {
+                referenced_names(param, &mut params);
+            }
+            let mut body = Vec::new();
+            referenced_names(&equation.guard, &mut body);
+            referenced_names(&equation.body, &mut body);
+            names.extend(body.into_iter().filter(|name| !params.contains(name)));
+        }
+        if let Some(Parameters::Lambda(lambda)) = scope.ram.get(&name) {
+            let mut body = Vec::new();
+            referenced_names(&lambda.body, &mut body);
+            names.extend(
+                body.into_iter()
+                    .filter(|name| !lambda.params.contains(name)),
+            );
+        }
+        for name in names {
+            if used.insert(name.clone()) {
+                pending.push(name);
+            }
+        }
+    }
+    used
+}
+
+/// Variables left unknown in a value, `x` and `y` in `2x + sin(y)`.
+fn unknown_names(value: &Parameters, names: &mut Vec<String>) {
+    match value {
+        Parameters::Identifier(s) | Parameters::Var(_, _, s) => names.push(s.clone()),
+        Parameters::Plus(a, b) | Parameters::Mul(a, b) | Parameters::Div(a, b) => {
+            unknown_names(a, names);
+            unknown_names(b, names);
+        }
+        Parameters::Call(_, arg) => unknown_names(arg, names),
+        Parameters::InterpreterVector(v) => v.iter().for_each(|x| unknown_names(x, names)),
+        _ => (),
+    }
+}
+
+/// Names a piece of code refers to, either as variables or as functions.
+fn referenced_names(ast: &Ast, names: &mut Vec<String>) {
+    match ast {
+        Ast::Nil => (),
+        Ast::Node { value, left, right } => {
+            match value {
+                Parameters::Identifier(s) => names.push(s.clone()),
+                Parameters::Vector(a) => a.iter().for_each(|x| referenced_names(x, names)),
+                Parameters::Lambda(lambda) => referenced_names(&lambda.body, names),
+                _ => (),
+            }
+            referenced_names(left, names);
+            referenced_names(right, names);
+        }
+        Ast::Call { name, lst } => {
+            names.push(name.clone());
+            lst.iter().for_each(|x| referenced_names(x, names));
+        }
+        Ast::If {
+            condition,
+            then,
+            otherwise,
+        } => {
+            referenced_names(condition, names);
+            referenced_names(then, names);
+            referenced_names(otherwise, names);
+        }
+        Ast::Block(statements) => statements.iter().for_each(|x| referenced_names(x, names)),
+        Ast::For { iterable, body, .. } => {
+            referenced_names(iterable, names);
+            referenced_names(body, names);
+        }
+        Ast::While { condition, body } => {
+            referenced_names(condition, names);
+            referenced_names(body, names);
+        }
+    }
+}
+
+/// Copies in the lambda the values of the local variables its body refers to,
+/// they would not outlive the call creating the lambda otherwise.
+fn capture(lambda: &Lambda, scope: &Scope) -> Lambda {
+    let mut names = Vec::new();
+    referenced_names(&lambda.body, &mut names);
+    let mut lambda = lambda.clone();
+    for name in names {
+        if lambda.params.contains(&name) || lambda.captured.contains_key(&name) {
+            continue;
+        }
+        if let Some(value) = scope.get_local(&name) {
+            lambda.captured.insert(name, value.clone());
+        }
+    }
+    lambda
+}
+
+/// Removes the variable and the function called `name` from the innermost
+/// scope.
+fn delete(name: &str, scope: &mut Scope) -> Parameters {
+    let variable = scope.ram.remove(name);
+    let fun = scope.functions.remove(name);
+    if fun.is_some() && scope.is_root() {
+        memo::forget(name);
+    }
+    if variable.is_none() && fun.is_none() {
+        return Parameters::Identifier(format!("@{name} is not defined"));
+    }
+    globals_changed(scope, &[name.to_string()]);
+    if !scope.echo {
+        return Parameters::Null;
+    }
+    println!(
+        "{}: {}",
+        ansi_term::Color::Cyan.paint("del"),
+        ansi_term::Color::Yellow.paint(name)
+    );
+    Parameters::Null
+}
+
+/// Caches the results of the global function `name`, which must be pure.
+fn memoize(name: &str, scope: &mut Scope) -> Parameters {
+    if !scope.root().functions.contains_key(name) {
+        return Parameters::Identifier(format!("@{name} is not a global function"));
+    }
+    memo::memoize(name);
+    if scope.echo {
+        println!(
+            "{}: {}",
+            ansi_term::Color::Cyan.paint("memo"),
+            ansi_term::Color::Yellow.paint(name)
+        );
+    }
+    Parameters::Null
+}
+
+/// Interprets statements in order and returns the value of the last one, it
+/// stops at the first error.
+pub fn interpret_statements(
+    statements: &[Ast],
+    ram: &mut Ram,
+    function: &mut Functions,
+) -> Parameters {
+    let mut scope = Scope::new(ram, function);
+    let mut last = Parameters::Null;
+    for statement in statements {
+        last = evaluate(statement, &mut scope);
+        if last.is_error() {
+            break;
+        }
+    }
+    last
+}
+
 #[cfg(test)]
 mod test {
     use std::collections::HashMap;
 
+    use num_complex::Complex64;
+
+    use crate::exact_math::programmer_mode::{IntType, Overflow, ProgrammerMode};
     use crate::exact_math::rationals::Rationals;
-    use crate::interpreting::interpreter::interpret;
-    use crate::parsing::ast::{Ast, Parameters};
+    use crate::interpreting::interpreter::{interpret, interpret_statements};
+    use crate::interpreting::memo;
+    use crate::lexing::lexer::lex;
+    use crate::parsing::ast::{Ast, Functions, Parameters};
+    use crate::parsing::parser::init_calc_parser;
+    use crate::{MAX_RECURSION_LIMIT, PROGRAMMER_MODE, RECURSION_LIMIT, STACK_SIZE};
 
     #[test]
     fn test_interpreter_int() {
         let mut ram: HashMap<String, Parameters> = HashMap::new();
-        let mut function: HashMap<String, (Vec<Ast>, Ast)> = HashMap::new();
+        let mut function: Functions = HashMap::new();
         let expected = Parameters::Int(2);
         let ast = Ast::Node {
             value: Parameters::Int(2),
@@ -165,7 +640,7 @@ mod test {
     #[test]
     fn test_interpreter_float() {
         let mut ram: HashMap<String, Parameters> = HashMap::new();
-        let mut function: HashMap<String, (Vec<Ast>, Ast)> = HashMap::new();
+        let mut function: Functions = HashMap::new();
         let expected = Parameters::Rational(Rationals::new(1, 2));
         let ast = Ast::Node {
             value: Parameters::Float(2.0),
@@ -179,7 +654,7 @@ mod test {
     #[test]
     fn test_interpreter_plus_operation() {
         let mut ram: HashMap<String, Parameters> = HashMap::new();
-        let mut function: HashMap<String, (Vec<Ast>, Ast)> = HashMap::new();
+        let mut function: Functions = HashMap::new();
         let expected = Parameters::Int(2);
         let ast = Ast::Node {
             value: Parameters::PlusOperation,
@@ -193,7 +668,7 @@ mod test {
     #[test]
     fn test_interpreter_minus_operation() {
         let mut ram: HashMap<String, Parameters> = HashMap::new();
-        let mut function: HashMap<String, (Vec<Ast>, Ast)> = HashMap::new();
+        let mut function: Functions = HashMap::new();
         let expected = Parameters::Int(0);
         let ast = Ast::Node {
             value: Parameters::MinusOperation,
@@ -207,7 +682,7 @@ mod test {
     #[test]
     fn test_interpreter_mult_operation() {
         let mut ram: HashMap<String, Parameters> = HashMap::new();
-        let mut function: HashMap<String, (Vec<Ast>, Ast)> = HashMap::new();
+        let mut function: Functions = HashMap::new();
         let expected = Parameters::Int(1);
         let ast = Ast::Node {
             value: Parameters::MultiplicationOperation,
@@ -221,9 +696,8 @@ mod test {
     #[test]
     fn test_interpreter_divide_operation() {
         let mut ram: HashMap<String, Parameters> = HashMap::new();
-        let mut function: HashMap<String, (Vec<Ast>, Ast)> = HashMap::new();
-        let expected =
-            Parameters::Rational(crate::exact_math::rationals::Rationals { under: 1, over: 1 });
+        let mut function: Functions = HashMap::new();
+        let expected = Parameters::Rational(crate::exact_math::rationals::Rationals::new(1, 1));
         let ast = Ast::Node {
             value: Parameters::DivideOperation,
             left: Box::from(Ast::new(Parameters::Int(1))),
@@ -232,4 +706,524 @@ mod test {
         let result = interpret(&ast, &mut ram, &mut function);
         assert_eq!(result, expected)
     }
+
+    #[test]
+    fn test_interpret_statements() {
+        let mut ram: HashMap<String, Parameters> = HashMap::new();
+        let mut function: Functions = HashMap::new();
+        let lexed = lex("a = 2; b = 3; a*b".to_string()).unwrap();
+        let statements = init_calc_parser(&lexed).parse_statements().unwrap();
+        let result = interpret_statements(&statements, &mut ram, &mut function);
+        assert_eq!(result, Parameters::Int(6));
+        assert_eq!(ram.get("b"), Some(&Parameters::Int(3)));
+    }
+
+    #[test]
+    fn test_interpret_if_is_lazy() {
+        let mut ram: HashMap<String, Parameters> = HashMap::new();
+        let mut function: Functions = HashMap::new();
+        let lexed = lex("if 1 > 2 then y = 1 else 2".to_string()).unwrap();
+        let statements = init_calc_parser(&lexed).parse_statements().unwrap();
+        let result = interpret_statements(&statements, &mut ram, &mut function);
+        assert_eq!(result, Parameters::Int(2));
+        assert_eq!(ram.get("y"), None);
+    }
+
+    #[test]
+    fn test_recursive_function() {
+        let mut ram: HashMap<String, Parameters> = HashMap::new();
+        let mut function: Functions = HashMap::new();
+        let input = "fib(n) = if n < 2 then n else fib(n-1)+fib(n-2); fib(15)";
+        let lexed = lex(input.to_string()).unwrap();
+        let statements = init_calc_parser(&lexed).parse_statements().unwrap();
+        let result = interpret_statements(&statements, &mut ram, &mut function);
+        assert_eq!(result, Parameters::Int(610));
+    }
+
+    #[test]
+    fn test_recursion_limit() {
+        RECURSION_LIMIT.with(|rl| *rl.borrow_mut() = 20);
+        let mut ram: HashMap<String, Parameters> = HashMap::new();
+        let mut function: Functions = HashMap::new();
+        let input = "f(n) = f(n+1) + f(n+2); f(0)";
+        let lexed = lex(input.to_string()).unwrap();
+        let statements = init_calc_parser(&lexed).parse_statements().unwrap();
+        let result = interpret_statements(&statements, &mut ram, &mut function);
+        let expected = "@maximum recursion depth of 20 exceeded".to_string();
+        assert_eq!(result, Parameters::Identifier(expected));
+
+        let lexed = lex("g(n) = if n == 0 then 0 else 1 + g(n-1); g(19)".to_string()).unwrap();
+        let statements = init_calc_parser(&lexed).parse_statements().unwrap();
+        let result = interpret_statements(&statements, &mut ram, &mut function);
+        assert_eq!(result, Parameters::Int(19));
+    }
+
+    #[test]
+    fn test_recursion_limit_is_capped() {
+        let result = on_calculator_stack(|| {
+            RECURSION_LIMIT.with(|rl| *rl.borrow_mut() = usize::MAX);
+            run("g(n) = if n == 0 then 0 else 1 + g(n-1); g(100000)")
+        });
+        let expected = format!("@maximum recursion depth of {MAX_RECURSION_LIMIT} exceeded");
+        assert_eq!(result, Parameters::Identifier(expected));
+    }
+
+    #[test]
+    fn test_redefine_and_delete() {
+        let mut ram: HashMap<String, Parameters> = HashMap::new();
+        let mut function: Functions = HashMap::new();
+        let input = "f(x) = x + 1; f(x) = x * 10; a = f(2); del f";
+        let lexed = lex(input.to_string()).unwrap();
+        let statements = init_calc_parser(&lexed).parse_statements().unwrap();
+        let result = interpret_statements(&statements, &mut ram, &mut function);
+        assert_eq!(result, Parameters::Null);
+        assert_eq!(ram.get("a"), Some(&Parameters::Int(20)));
+        assert!(!function.contains_key("f"));
+
+        let lexed = lex("del a; del a".to_string()).unwrap();
+        let statements = init_calc_parser(&lexed).parse_statements().unwrap();
+        let result = interpret_statements(&statements, &mut ram, &mut function);
+        let expected = Parameters::Identifier("@a is not defined".to_string());
+        assert_eq!(result, expected);
+        assert_eq!(ram.get("a"), None);
+    }
+
+    #[test]
+    fn test_functions_see_globals() {
+        let mut ram: HashMap<String, Parameters> = HashMap::new();
+        let mut function: Functions = HashMap::new();
+        let input = "k = 3; x = 10; f(x) = k*x; g(y) = f(y) + x; g(2)";
+        let lexed = lex(input.to_string()).unwrap();
+        let statements = init_calc_parser(&lexed).parse_statements().unwrap();
+        let result = interpret_statements(&statements, &mut ram, &mut function);
+        assert_eq!(result, Parameters::Int(16));
+        assert_eq!(ram.get("y"), None);
+    }
+
+    fn run(input: &str) -> Parameters {
+        let mut ram: HashMap<String, Parameters> = HashMap::new();
+        let mut function: Functions = HashMap::new();
+        let lexed = lex(input.to_string()).unwrap();
+        let statements = init_calc_parser(&lexed).parse_statements().unwrap();
+        interpret_statements(&statements, &mut ram, &mut function)
+    }
+
+    /// Runs `f` in a thread whose stack is as large as the one of the
+    /// calculator, for deep recursions.
+    fn on_calculator_stack<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> T {
+        std::thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn(f)
+            .unwrap()
+            .join()
+            .unwrap()
+    }
+
+    fn vector(v: &[i64]) -> Parameters {
+        Parameters::InterpreterVector(Box::from(
+            v.iter().map(|x| Parameters::Int(*x)).collect::<Vec<_>>(),
+        ))
+    }
+
+    #[test]
+    fn test_lambdas() {
+        assert_eq!(run("sq = x -> x*x; sq(5)"), Parameters::Int(25));
+        assert_eq!(run("sub = (a, b) -> a - b; sub(7, 2)"), Parameters::Int(5));
+        assert_eq!(
+            run("twice(f, x) = f(f(x)); twice(x -> 3x, 2)"),
+            Parameters::Int(18)
+        );
+        assert_eq!(
+            run("twice(f, x) = f(f(x)); twice(abs, -2)"),
+            Parameters::Int(2)
+        );
+        assert_eq!(run("(x -> x + 1)(2)"), Parameters::Int(3));
+        assert_eq!(run("((a, b) -> a * b)(3, 4)"), Parameters::Int(12));
+        assert_eq!(run("add(x) = y -> x + y; add(1)(2)"), Parameters::Int(3));
+    }
+
+    #[test]
+    fn test_builtins_cannot_be_redefined() {
+        for input in [
+            "map(f, v) = v",
+            "zip(a) = a",
+            "apply = x -> x",
+            "fact(n) = n",
+            "index(v, i) = 0",
+            "slice(v, i, j) = v",
+            "range(n) = n",
+        ] {
+            let name = input.split(['(', ' ']).next().unwrap();
+            let expected = format!("@{name} is a built-in function and cannot be redefined");
+            assert_eq!(run(input), Parameters::Identifier(expected), "{input}");
+        }
+        assert_eq!(run("sin = 3; sin"), Parameters::Int(3));
+    }
+
+    #[test]
+    fn test_lambda_captures_locals() {
+        let input = "adder(k) = x -> x + k; add3 = adder(3); k = 100; add3(4)";
+        assert_eq!(run(input), Parameters::Int(7));
+        assert_eq!(run("f = x -> x + k; k = 1; f(1)"), Parameters::Int(2));
+    }
+
+    #[test]
+    fn test_higher_order_functions() {
+        assert_eq!(run("map(x -> x^2, [1, 2, 3])"), vector(&[1, 4, 9]));
+        assert_eq!(
+            run("filter(x -> x mod 2 == 0, [1, 2, 3, 4])"),
+            vector(&[2, 4])
+        );
+        assert_eq!(
+            run("reduce((a, b) -> a + b, [1, 2, 3, 4])"),
+            Parameters::Int(10)
+        );
+        assert_eq!(
+            run("fold((a, b) -> a*b, [1, 2, 3], 10)"),
+            Parameters::Int(60)
+        );
+        assert_eq!(run("apply((a, b) -> a - b, [10, 3])"), Parameters::Int(7));
+        let expected =
+            Parameters::InterpreterVector(Box::from(vec![vector(&[1, 3]), vector(&[2, 4])]));
+        assert_eq!(run("zip([1, 2], [3, 4, 5])"), expected);
+        let expected = Parameters::Identifier("@cannot reduce an empty vector".to_string());
+        assert_eq!(run("reduce((a, b) -> a + b, [])"), expected);
+    }
+
+    #[test]
+    fn test_indexing() {
+        assert_eq!(run("v = [10, 20, 30]; v[0] + v[-1]"), Parameters::Int(40));
+        assert_eq!(run("m = [[1, 2], [3, 4]]; m[1][0]"), Parameters::Int(3));
+        assert_eq!(run("v = [10, 20, 30]; v[1:]"), vector(&[20, 30]));
+        assert_eq!(run("v = [10, 20, 30]; v[2:1]"), vector(&[]));
+        assert_eq!(run("v = [10, 20, 30]; v[-3:3]"), vector(&[10, 20, 30]));
+        let expected = "@slice bound -5 is out of bounds for a vector of length 3".to_string();
+        assert_eq!(
+            run("v = [10, 20, 30]; v[:-5]"),
+            Parameters::Identifier(expected)
+        );
+        let expected = "@slice bound 5 is out of bounds for a vector of length 3".to_string();
+        assert_eq!(run("[1, 2, 3][5:9]"), Parameters::Identifier(expected));
+        let expected =
+            "@index 1180591620717411303424 is out of bounds for a vector of length 3".to_string();
+        assert_eq!(run("[1, 2, 3][2^70]"), Parameters::Identifier(expected));
+        let expected =
+            "@slice bound -1180591620717411303424 is out of bounds for a vector of length 3";
+        assert_eq!(
+            run("[1, 2, 3][-2^70:]"),
+            Parameters::Identifier(expected.to_string())
+        );
+        assert_eq!(run("[10, 20, 30][4/2]"), Parameters::Int(30));
+        let expected = "@indices must be integers, not 3/2".to_string();
+        assert_eq!(run("[10, 20, 30][3/2]"), Parameters::Identifier(expected));
+        let expected = "@index 3 is out of bounds for a vector of length 3".to_string();
+        assert_eq!(run("[10, 20, 30][3]"), Parameters::Identifier(expected));
+    }
+
+    #[test]
+    fn test_ranges() {
+        assert_eq!(run("1..4"), vector(&[1, 2, 3]));
+        assert_eq!(run("range(3)"), vector(&[0, 1, 2]));
+        assert_eq!(run("range(10, 0, -4)"), vector(&[10, 6, 2]));
+        assert_eq!(run("4..1"), vector(&[]));
+        let expected = "@the step of a range cannot be zero".to_string();
+        assert_eq!(run("range(1, 2, 0)"), Parameters::Identifier(expected));
+    }
+
+    #[test]
+    fn test_programmer_mode_fits_every_result() {
+        let u8 = IntType::parse("u8").unwrap();
+        PROGRAMMER_MODE.with(|pm| *pm.borrow_mut() = ProgrammerMode::Fixed(u8, Overflow::Wrapping));
+        assert_eq!(run("2^9"), Parameters::Int(0));
+        assert_eq!(run("fact(6)"), Parameters::Int(208));
+        assert_eq!(run("300"), Parameters::Int(44));
+        assert_eq!(run("[300, 2^8 + 3]"), vector(&[44, 3]));
+        PROGRAMMER_MODE.with(|pm| *pm.borrow_mut() = ProgrammerMode::Fixed(u8, Overflow::Checked));
+        let expected = "@300 overflows u8".to_string();
+        assert_eq!(run("300"), Parameters::Identifier(expected));
+        let u128 = IntType::parse("u128").unwrap();
+        PROGRAMMER_MODE
+            .with(|pm| *pm.borrow_mut() = ProgrammerMode::Fixed(u128, Overflow::Wrapping));
+        assert_eq!(run("2^128"), Parameters::Int(0));
+        let i8 = IntType::parse("i8").unwrap();
+        for overflow in [Overflow::Wrapping, Overflow::Saturating, Overflow::Checked] {
+            PROGRAMMER_MODE.with(|pm| *pm.borrow_mut() = ProgrammerMode::Fixed(i8, overflow));
+            assert_eq!(run("-128"), Parameters::Int(-128), "{overflow:?}");
+            assert_eq!(run("x = -128; x"), Parameters::Int(-128), "{overflow:?}");
+        }
+        assert_eq!(
+            run("-128 - 1"),
+            Parameters::Identifier("@-129 overflows i8".to_string())
+        );
+        PROGRAMMER_MODE.with(|pm| *pm.borrow_mut() = ProgrammerMode::Fixed(u8, Overflow::Wrapping));
+        assert_eq!(run("7/2"), Parameters::Int(3));
+        PROGRAMMER_MODE.with(|pm| *pm.borrow_mut() = ProgrammerMode::Off);
+        assert_eq!(run("300"), Parameters::Int(300));
+        assert_eq!(run("7/2"), Parameters::Rational(Rationals::new(2, 7)));
+    }
+
+    #[test]
+    fn test_modulo_negative_operands() {
+        assert_eq!(run("7 % -3"), Parameters::Int(-2));
+        assert_eq!(run("-7 % -3"), Parameters::Int(-1));
+        assert_eq!(run("x = 5; x % -2"), Parameters::Int(-1));
+        assert_eq!(run("50% - 3"), run("1/2 - 3"));
+    }
+
+    #[test]
+    fn test_derivative_at_a_point() {
+        assert_eq!(run("f(x) = x * x; f'(3)"), Parameters::Int(6));
+        assert_eq!(run("f(x) = x * x * x; f'(2)"), Parameters::Int(12));
+        assert_eq!(run("(x -> x * x)'(5)"), Parameters::Int(10));
+        assert_eq!(run("sin'(0)"), Parameters::Float(1.0));
+        assert_eq!(run("f(x) = x^2; f'(3)"), Parameters::Int(6));
+        assert_eq!(run("f(x) = 3x^2 + 2x; f'(1)"), Parameters::Int(8));
+        assert_eq!(run("f(x) = x^3 - 4x + 1; f'(2)"), Parameters::Int(8));
+        assert_eq!(run("f(x) = (x + 1)^2; f'(1)"), Parameters::Int(4));
+        assert_eq!(run("f(x) = 1/x; f'(2)"), run("-1/4"));
+        assert_eq!(run("f(x) = x^-2; f'(1)"), Parameters::Int(-2));
+    }
+
+    #[test]
+    fn test_factorial_before_equality() {
+        assert_eq!(run("5! == 120"), Parameters::Bool(true));
+        assert_eq!(run("(5!)==120"), Parameters::Bool(true));
+        assert_eq!(run("2 != 3"), Parameters::Bool(true));
+        assert_eq!(run("(1+2)!=3"), Parameters::Bool(false));
+        assert_eq!(run("true!=false"), Parameters::Bool(true));
+        assert_eq!(run("n = 2; n!=0"), Parameters::Bool(true));
+    }
+
+    #[test]
+    fn test_imaginary_unit() {
+        let complex = |re, im| Parameters::Complex(Complex64::new(re, im));
+        assert_eq!(run("2 + 3i"), complex(2.0, 3.0));
+        assert_eq!(run("i * i"), Parameters::Float(-1.0));
+        assert_eq!(run("i = 3; 2 + 3i"), complex(2.0, 3.0));
+        assert_eq!(run("i = 3; del i; i"), complex(0.0, 1.0));
+        assert_eq!(run("for i in 1..3 {}; i"), complex(0.0, 1.0));
+        assert!(matches!(run("asin(2)"), Parameters::Complex(c) if c.im != 0.0));
+        assert!(matches!(run("acos(-3)"), Parameters::Complex(c) if c.im != 0.0));
+    }
+
+    #[test]
+    fn test_functions_of_big_integers() {
+        assert_eq!(run("sqrt(2^200)"), Parameters::Float(2f64.powi(100)));
+        assert_eq!(run("ln(2^100)"), Parameters::Float(2f64.powi(100).ln()));
+        assert_eq!(run("sin(2^70)"), Parameters::Float(2f64.powi(70).sin()));
+        assert_eq!(run("norm([2^70, 0])"), Parameters::Float(2f64.powi(70)));
+        let expected = "@factorial of 18446744073709551616 is too large".to_string();
+        assert_eq!(run("fact(2^64)"), Parameters::Identifier(expected));
+        assert_eq!(run("(4/2)!"), Parameters::Int(2));
+        assert_eq!(run("fact(3.0)"), Parameters::Int(6));
+        let expected = "@factorial of 5/2 which is not an integer".to_string();
+        assert_eq!(run("(5/2)!"), Parameters::Identifier(expected));
+    }
+
+    #[test]
+    fn test_blocks() {
+        assert_eq!(run("{ a = 2; a * 3 }"), Parameters::Int(6));
+        assert_eq!(
+            run("{ a = 2; a * 3 }; a"),
+            Parameters::Identifier("a".to_string())
+        );
+        assert_eq!(run("a = 1; { a = a + 1 }; a"), Parameters::Int(2));
+        assert_eq!(run("{ g(x) = x; g(1) }; g(1)"), run("g(1)"));
+        assert_eq!(run("f(n) = { t = n * 2; t + 1 }; f(4)"), Parameters::Int(9));
+    }
+
+    #[test]
+    fn test_loops() {
+        assert_eq!(
+            run("s = 0; for i in 1..5 { s = s + i }; s"),
+            Parameters::Int(10)
+        );
+        assert_eq!(run("i = 7; for i in [1, 2] {}; i"), Parameters::Int(7));
+        assert_eq!(
+            run("n = 1; while n < 100 { n = 2n }; n"),
+            Parameters::Int(128)
+        );
+        assert_eq!(
+            run("prod(n) = { r = 1; for k in 1..n+1 { r = r * k }; r }; prod(6)"),
+            Parameters::Int(720)
+        );
+        let expected = "@the condition of a while must be a boolean".to_string();
+        assert_eq!(run("while 1 {}"), Parameters::Identifier(expected));
+        let expected = "@only vectors can be iterated, not 3".to_string();
+        assert_eq!(run("for i in 3 {}"), Parameters::Identifier(expected));
+    }
+
+    #[test]
+    fn test_compound_assignment() {
+        assert_eq!(run("x = 1; x += 4; x *= 3; x -= 5; x"), Parameters::Int(10));
+        assert_eq!(
+            run("s = 0; for i in 1..4 { s += i*i }; s"),
+            Parameters::Int(14)
+        );
+        assert_eq!(
+            run("x = 17; x %= 5; y = 17; y //= 5; z = 2; z ^= 3; [x, y, z]"),
+            vector(&[2, 3, 8])
+        );
+        assert_eq!(
+            run("[a, b] = [1, 2]; [a, b] += [10, 20]; b"),
+            Parameters::Int(22)
+        );
+        let expected = "@undefined variable y".to_string();
+        assert_eq!(run("y += 1"), Parameters::Identifier(expected));
+        let expected = "@undefined variable a".to_string();
+        assert_eq!(run("[a, b] += [1, 2]"), Parameters::Identifier(expected));
+    }
+
+    #[test]
+    fn test_destructuring() {
+        assert_eq!(run("[a, b] = [1, 2]; [b, a]"), vector(&[2, 1]));
+        assert_eq!(
+            run("[a, [b, c]] = [1, [2, 3]]; a + b*c"),
+            Parameters::Int(7)
+        );
+        let expected = "@cannot destructure a vector of length 3 into 2 targets".to_string();
+        assert_eq!(run("[a, b] = [1, 2, 3]"), Parameters::Identifier(expected));
+        let expected = "@only vectors can be destructured, not 5".to_string();
+        assert_eq!(run("[a, b] = 5"), Parameters::Identifier(expected));
+        let expected = "@a cannot be defined in terms of itself".to_string();
+        assert_eq!(run("[a, b] = [b, a]"), Parameters::Identifier(expected));
+        let expected = "@x cannot be defined in terms of itself".to_string();
+        assert_eq!(run("x = x + 1"), Parameters::Identifier(expected));
+        assert_eq!(
+            run("[a, b] = [1, 2]; [a, b] = [b, a]; [a, b]"),
+            vector(&[2, 1])
+        );
+    }
+
+    #[test]
+    fn test_pattern_matching_definitions() {
+        assert_eq!(
+            run("fact2(0) = 1; fact2(n) = n*fact2(n-1); fact2(5)"),
+            Parameters::Int(120)
+        );
+        let sign = "sign(x) when x < 0 = -1; sign(0) = 0; sign(x) = 1;";
+        assert_eq!(
+            run(&format!("{sign} [sign(-4), sign(0), sign(9)]")),
+            vector(&[-1, 0, 1])
+        );
+        // an equation for the same case replaces the previous one
+        assert_eq!(
+            run(&format!("{sign} sign(y) = 2; sign(3)")),
+            Parameters::Int(2)
+        );
+        let expected = "@no equation of g matches the arguments".to_string();
+        assert_eq!(run("g(0) = 1; g(2)"), Parameters::Identifier(expected));
+        let expected = "@the guard of h must be a boolean".to_string();
+        assert_eq!(
+            run("h(x) when x = 1; h(2)"),
+            Parameters::Identifier(expected)
+        );
+    }
+
+    #[test]
+    fn test_default_and_named_arguments() {
+        let f = "f(x, n=2) = x^n;";
+        assert_eq!(run(&format!("{f} f(3)")), Parameters::Int(9));
+        assert_eq!(run(&format!("{f} f(3, 3)")), Parameters::Int(27));
+        assert_eq!(run(&format!("{f} f(n=1, x=5)")), Parameters::Int(5));
+        assert_eq!(run("g(a, b=a+1) = a*b; g(3)"), Parameters::Int(12));
+        assert_eq!(run("sq = x -> x*x; sq(x=4)"), Parameters::Int(16));
+        let expected = "@f has no parameter called m".to_string();
+        assert_eq!(
+            run(&format!("{f} f(1, m=2)")),
+            Parameters::Identifier(expected)
+        );
+        let expected = "@f got several values for x".to_string();
+        assert_eq!(
+            run(&format!("{f} f(x=1, x=2)")),
+            Parameters::Identifier(expected)
+        );
+        let expected = "@sqrt does not take named arguments".to_string();
+        assert_eq!(run("sqrt(x=4)"), Parameters::Identifier(expected));
+    }
+
+    #[test]
+    fn test_variadic_parameters() {
+        assert_eq!(
+            run("s(...args) = fold((a, b) -> a + b, args, 0); s(1, 2, 3, 4)"),
+            Parameters::Int(10)
+        );
+        assert_eq!(run("h(x, ...rest) = rest; h(1, 2, 3)"), vector(&[2, 3]));
+        assert_eq!(run("h(x, ...rest) = rest; h(1)"), vector(&[]));
+        let expected = "@...a can only be the last parameter of a function".to_string();
+        assert_eq!(run("k(...a, b) = 1"), Parameters::Identifier(expected));
+    }
+
+    #[test]
+    fn test_arity_errors() {
+        let expected = "@f takes 1 to 2 arguments but 3 were given".to_string();
+        assert_eq!(
+            run("f(x, n=2) = x^n; f(1, 2, 3)"),
+            Parameters::Identifier(expected)
+        );
+        let expected = "@g takes 2 arguments but 1 was given".to_string();
+        assert_eq!(run("g(a, b) = a; g(1)"), Parameters::Identifier(expected));
+        let expected = "@sq takes 1 argument but 2 were given".to_string();
+        assert_eq!(
+            run("sq = x -> x*x; sq(1, 2)"),
+            Parameters::Identifier(expected)
+        );
+        let expected = "@cos takes 1 to 2 arguments but 3 were given".to_string();
+        assert_eq!(run("cos(1, 2, 3)"), Parameters::Identifier(expected));
+        let expected = "@map takes 2 arguments but 1 was given".to_string();
+        assert_eq!(run("map(x -> x)"), Parameters::Identifier(expected));
+    }
+
+    #[test]
+    fn test_memoized_functions() {
+        let fib = "fib(0) = 0; fib(1) = 1; fib(n) = fib(n-1) + fib(n-2); memo fib;";
+        assert_eq!(
+            on_calculator_stack(move || run(&format!("{fib} fib(90)"))),
+            Parameters::Int(2880067194370816120)
+        );
+        // redefining a function empties its cache
+        assert_eq!(
+            run(&format!("{fib} fib(10); fib(n) = n; fib(10)")),
+            Parameters::Int(10)
+        );
+        let expected = "@g is not a global function".to_string();
+        assert_eq!(run("memo g"), Parameters::Identifier(expected));
+    }
+
+    #[test]
+    fn test_memoized_functions_see_global_changes() {
+        assert_eq!(
+            run("k = 2; f(x) = k*x; memo f; f(3); k = 5; f(3)"),
+            Parameters::Int(15)
+        );
+        assert_eq!(
+            run("g(x) = x; f(x) = g(x); memo f; f(1); g(x) = 2x; f(1)"),
+            Parameters::Int(2)
+        );
+        assert_eq!(
+            run("f(x) = x + j; memo f; s = 0; for j in [1, 2] { s += f(0) }; s"),
+            Parameters::Int(3)
+        );
+        assert_eq!(
+            run("k = 1; f(x) = k; memo f; { k = 7; f(0) }; del k; k = 4; f(0)"),
+            Parameters::Int(4)
+        );
+        assert_eq!(
+            run("k = 1; l = y -> y + k; f(x) = l(x); memo f; f(1); k = 5; f(1)"),
+            Parameters::Int(6)
+        );
+    }
+
+    #[test]
+    fn test_memoized_functions_keep_unrelated_results() {
+        let script = "f(x) = x^2; memo f; s = 0; for i in [1, 2, 3] { s += f(i) }; k = 1; s";
+        assert_eq!(run(script), Parameters::Int(14));
+        assert_eq!(memo::sizes(), vec![("f".to_string(), 3)]);
+        // the parameter `k` shadows the global
+        run("g(k) = k + 1; memo g; g(1); k = 2");
+        assert_eq!(
+            memo::sizes(),
+            vec![("f".to_string(), 3), ("g".to_string(), 1)]
+        );
+    }
 }
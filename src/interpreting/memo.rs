@@ -0,0 +1,128 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::parsing::ast::Parameters;
+
+/// Results of a memoized function keyed on its arguments.
+type Cache = HashMap<String, Parameters>;
+
+thread_local! {static CACHES: RefCell<HashMap<String, Cache>> = RefCell::new(HashMap::new())}
+
+/// Only calls whose arguments are all numbers are cached, the result of a
+/// call on an unknown variable depends on what it is bound to later.
+fn key(args: &[Parameters]) -> Option<String> {
+    fn is_number(p: &Parameters) -> bool {
+        match p {
+            Parameters::Int(_)
+            | Parameters::Big(_)
+            | Parameters::Float(_)
+            | Parameters::Rational(_)
+            | Parameters::Complex(_)
+            | Parameters::Bool(_) => true,
+            Parameters::InterpreterVector(v) => v.iter().all(is_number),
+            _ => false,
+        }
+    }
+    args.iter().all(is_number).then(|| format!("{args:?}"))
+}
+
+/// Starts caching the results of the global function `name`.
+pub fn memoize(name: &str) {
+    CACHES.with(|c| {
+        c.borrow_mut().entry(name.to_string()).or_default();
+    });
+}
+
+pub fn is_memoized(name: &str) -> bool {
+    CACHES.with(|c| c.borrow().contains_key(name))
+}
+
+pub fn get(name: &str, args: &[Parameters]) -> Option<Parameters> {
+    let key = key(args)?;
+    CACHES.with(|c| c.borrow().get(name)?.get(&key).cloned())
+}
+
+pub fn insert(name: &str, args: &[Parameters], value: Parameters) {
+    let Some(key) = key(args) else {
+        return;
+    };
+    CACHES.with(|c| {
+        if let Some(cache) = c.borrow_mut().get_mut(name) {
+            cache.insert(key, value);
+        }
+    });
+}
+
+/// Stops memoizing `name`, which no longer exists.
+pub fn forget(name: &str) {
+    CACHES.with(|c| c.borrow_mut().remove(name));
+}
+
+/// Empties every cache, the functions stay memoized. Their results may depend
+/// on a mode.
+pub fn clear() {
+    CACHES.with(|c| c.borrow_mut().values_mut().for_each(|cache| cache.clear()));
+}
+
+/// Empties the caches of the functions for which `stale` holds, after a
+/// global they depend on changed.
+pub fn clear_where(stale: impl Fn(&str) -> bool) {
+    CACHES.with(|c| {
+        c.borrow_mut()
+            .iter_mut()
+            .filter(|(name, _)| stale(name))
+            .for_each(|(_, cache)| cache.clear())
+    });
+}
+
+/// Stops memoizing every function.
+pub fn reset() {
+    CACHES.with(|c| c.borrow_mut().clear());
+}
+
+/// The memoized functions sorted by name, with the number of cached results.
+pub fn sizes() -> Vec<(String, usize)> {
+    let mut sizes: Vec<(String, usize)> = CACHES.with(|c| {
+        c.borrow()
+            .iter()
+            .map(|(name, cache)| (name.clone(), cache.len()))
+            .collect()
+    });
+    sizes.sort();
+    sizes
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parsing::ast::Parameters;
+
+    use super::{clear, clear_where, get, insert, memoize, sizes};
+
+    #[test]
+    pub fn test_only_numbers_are_cached() {
+        memoize("f");
+        insert("f", &[Parameters::Int(2)], Parameters::Int(4));
+        insert(
+            "f",
+            &[Parameters::Identifier("x".to_string())],
+            Parameters::Null,
+        );
+        insert("g", &[Parameters::Int(2)], Parameters::Int(5));
+        assert_eq!(get("f", &[Parameters::Int(2)]), Some(Parameters::Int(4)));
+        assert_eq!(get("g", &[Parameters::Int(2)]), None);
+        assert_eq!(sizes(), vec![("f".to_string(), 1)]);
+        clear();
+        assert_eq!(get("f", &[Parameters::Int(2)]), None);
+        assert_eq!(sizes(), vec![("f".to_string(), 0)]);
+    }
+
+    #[test]
+    pub fn test_clear_only_stale_caches() {
+        memoize("f");
+        memoize("g");
+        insert("f", &[Parameters::Int(1)], Parameters::Int(1));
+        insert("g", &[Parameters::Int(1)], Parameters::Int(2));
+        clear_where(|name| name == "g");
+        assert_eq!(sizes(), vec![("f".to_string(), 1), ("g".to_string(), 0)]);
+    }
+}
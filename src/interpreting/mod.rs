@@ -1,2 +1,4 @@
 pub(crate) mod interpreter;
+pub(crate) mod memo;
+pub(crate) mod scope;
 pub mod stdlib;
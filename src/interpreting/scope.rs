@@ -0,0 +1,103 @@
+use crate::parsing::ast::{Equation, Functions, Parameters, Ram};
+
+/// The variables and functions visible from a piece of code. Lookups which
+/// fail in the maps of a scope continue in its parent, definitions only ever
+/// go in the innermost maps.
+pub struct Scope<'a> {
+    pub ram: &'a mut Ram,
+    pub functions: &'a mut Functions,
+    /// Whether definitions and deletions are printed, they are only for
+    /// statements typed at the top level.
+    pub echo: bool,
+    parent: Option<&'a Scope<'a>>,
+}
+
+impl<'a> Scope<'a> {
+    pub fn new(ram: &'a mut Ram, functions: &'a mut Functions) -> Self {
+        Scope {
+            ram,
+            functions,
+            echo: true,
+            parent: None,
+        }
+    }
+
+    pub fn child(ram: &'a mut Ram, functions: &'a mut Functions, parent: &'a Scope<'a>) -> Self {
+        Scope {
+            ram,
+            functions,
+            echo: false,
+            parent: Some(parent),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Parameters> {
+        match self.ram.get(name) {
+            Some(value) => Some(value),
+            None => self.parent.and_then(|p| p.get(name)),
+        }
+    }
+
+    /// Like `get` but ignores the global scope, the root of the chain.
+    pub fn get_local(&self, name: &str) -> Option<&Parameters> {
+        let parent = self.parent?;
+        match self.ram.get(name) {
+            Some(value) => Some(value),
+            None => parent.get_local(name),
+        }
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.parent.is_none()
+    }
+
+    pub fn root(&self) -> &Scope<'a> {
+        match self.parent {
+            None => self,
+            Some(parent) => parent.root(),
+        }
+    }
+
+    /// Finds the equations of the function `name` along with the scope they
+    /// were defined in.
+    pub fn get_function(&self, name: &str) -> Option<(&Scope<'a>, &[Equation])> {
+        match self.functions.get(name) {
+            Some(equations) => Some((self, equations)),
+            None => self.parent.and_then(|p| p.get_function(name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::parsing::ast::{Ast, Equation, Parameters};
+
+    use super::Scope;
+
+    #[test]
+    pub fn test_lookup_goes_through_parents() {
+        let mut globals = HashMap::from([
+            ("k".to_string(), Parameters::Int(3)),
+            ("x".to_string(), Parameters::Int(1)),
+        ]);
+        let mut global_functions =
+            HashMap::from([("f".to_string(), vec![Equation::new(vec![], Ast::Nil)])]);
+        let global = Scope::new(&mut globals, &mut global_functions);
+
+        let mut locals = HashMap::from([("x".to_string(), Parameters::Int(2))]);
+        let mut local_functions = HashMap::new();
+        let local = Scope::child(&mut locals, &mut local_functions, &global);
+
+        assert_eq!(local.get("x"), Some(&Parameters::Int(2)));
+        assert_eq!(local.get("k"), Some(&Parameters::Int(3)));
+        assert_eq!(local.get("y"), None);
+        assert_eq!(local.get_local("k"), None);
+        assert_eq!(local.get_local("x"), Some(&Parameters::Int(2)));
+        assert!(local.root().parent.is_none());
+        assert!(global.echo && !local.echo);
+        let (owner, _) = local.get_function("f").unwrap();
+        assert!(owner.parent.is_none());
+    }
+}
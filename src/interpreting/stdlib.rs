@@ -1,27 +1,382 @@
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::f64::consts::{E, PI};
 
 use gnuplot::{AxesCommon, Figure};
+use num_bigint::BigInt;
+use num_complex::Complex64;
+use num_traits::{One, Signed, ToPrimitive};
 
 use crate::configuration::loader::{load, load_config, Config};
+use crate::exact_math::radix_mode::RadixMode;
 use crate::functions::divide::divide;
+use crate::functions::expo::expo;
+use crate::functions::function::{equal, greater, lesser};
 use crate::functions::minus::minus;
-use crate::interpreting::interpreter::interpret;
+use crate::interpreting::interpreter::{evaluate, interpret};
+use crate::interpreting::memo;
+use crate::interpreting::scope::Scope;
 use crate::parsing::ast::{self};
 use crate::parsing::ast::{
-    Ast,
+    Ast, Equation,
     Parameters::{self, *},
 };
+use crate::utils::complex_utils::from_complex;
+use crate::utils::integer_utils::{big_to_f64, from_big, to_radix_string};
 use crate::utils::matrix_utils::{lup_decompose, lup_determinant, lup_invert, transpose};
 use crate::utils::plot_utils::computes_lines;
+use crate::{MAX_RECURSION_LIMIT, RECURSION_LIMIT};
 
 use crate::functions::add::add as other_add;
+use crate::functions::modulo::divmod as other_divmod;
 use crate::functions::mult::mult;
 
 type Ram<'a> = Option<&'a mut ast::Ram>;
 type Functions<'a> = Option<&'a mut ast::Functions>;
 
-pub fn exec(s: String, lst: Vec<Parameters>, ram: Ram, functions: Functions) -> Parameters {
+thread_local! {static CALL_DEPTH: Cell<usize> = const {Cell::new(0)}}
+thread_local! {static DEPTH_EXCEEDED: Cell<bool> = const {Cell::new(false)}}
+
+/// Name under which `diff` and `plot` treat a lambda like a user function.
+const LAMBDA_NAME: &str = "<lambda>";
+
+/// The equation of a lambda, as it is stored in `Functions`.
+fn lambda_definition(lambda: &ast::Lambda) -> Equation {
+    let params = lambda
+        .params
+        .iter()
+        .map(|name| Ast::Node {
+            value: Identifier(name.clone()),
+            left: Box::from(Ast::Nil),
+            right: Box::from(Ast::Nil),
+        })
+        .collect();
+    Equation::new(params, lambda.body.clone())
+}
+
+/// Interprets `body` in a new child scope of `parent` where `names` are bound
+/// to `lst`, one level deeper in the call stack. Once the recursion limit is
+/// hit every pending call fails right away so that branching recursions
+/// unwind quickly.
+fn invoke(
+    parent: &Scope,
+    names: &[String],
+    lst: Vec<Parameters>,
+    body: &Ast,
+    mut sram: ast::Ram,
+) -> Parameters {
+    let depth = CALL_DEPTH.with(|d| d.get());
+    if depth == 0 {
+        DEPTH_EXCEEDED.with(|e| e.set(false));
+    }
+    let limit = RECURSION_LIMIT
+        .with(|rl| *rl.borrow())
+        .min(MAX_RECURSION_LIMIT);
+    if depth >= limit || DEPTH_EXCEEDED.with(|e| e.get()) {
+        DEPTH_EXCEEDED.with(|e| e.set(true));
+        return Identifier(format!("@maximum recursion depth of {limit} exceeded"));
+    }
+
+    names
+        .iter()
+        .zip(lst)
+        .filter(|(name, param)| match param {
+            Parameters::Identifier(s) => s.as_str() != name.as_str(),
+            _ => true,
+        })
+        .for_each(|(name, param)| {
+            sram.insert(name.to_string(), param);
+        });
+    let mut sfunctions: ast::Functions = HashMap::new();
+    let mut local = Scope::child(&mut sram, &mut sfunctions, parent);
+
+    CALL_DEPTH.with(|d| d.set(depth + 1));
+    let result = evaluate(body, &mut local);
+    CALL_DEPTH.with(|d| d.set(depth));
+    result
+}
+
+/// Arguments of a call given by name, `f(x=3)`.
+type Named = Vec<(String, Parameters)>;
+
+/// The parameter names of a call along with the values bound to them.
+type Bound = (Vec<String>, Vec<Parameters>);
+
+/// A lambda runs in a child of the global scope, with its captured variables.
+/// `name` is the variable holding it, if any, for the error messages.
+fn call_lambda(
+    name: &str,
+    lambda: &ast::Lambda,
+    lst: Vec<Parameters>,
+    named: &Named,
+    scope: &Scope,
+) -> Parameters {
+    let params = lambda_definition(lambda).params;
+    match bind_arguments(name, scope.root(), &params, lst, named) {
+        Ok(Some((names, args))) => invoke(
+            scope.root(),
+            &names,
+            args,
+            &lambda.body,
+            lambda.captured.clone(),
+        ),
+        Ok(None) => Null,
+        Err(error) => error,
+    }
+}
+
+/// Calls the user function `name`, which runs in a child of the scope it was
+/// defined in, or the function held by the variable `name`.
+fn call_function(name: &str, lst: Vec<Parameters>, named: Named, scope: &mut Scope) -> Parameters {
+    if let Some((owner, equations)) = scope.get_function(name) {
+        // only the global functions are memoized, by name
+        if !(owner.is_root() && named.is_empty() && memo::is_memoized(name)) {
+            return call_equations(name, owner, equations, lst, &named);
+        }
+        if let Some(value) = memo::get(name, &lst) {
+            return value;
+        }
+        let value = call_equations(name, owner, equations, lst.clone(), &named);
+        if !value.is_error() {
+            memo::insert(name, &lst, value.clone());
+        }
+        return value;
+    }
+    match scope.get(name).cloned() {
+        Some(Lambda(lambda)) => call_lambda(name, &lambda, lst, &named, scope),
+        Some(Identifier(other)) if other != name => exec_named(other, lst, named, scope),
+        _ => Identifier("@This function is unknown".to_string()),
+    }
+}
+
+/// Describes how many arguments a function takes, `2 arguments`, `1 to 3
+/// arguments` or `at least 1 argument`.
+fn describe_arity(min: usize, max: Option<usize>) -> String {
+    let noun = |n: usize| if n == 1 { "argument" } else { "arguments" };
+    match max {
+        Some(max) if max == min => format!("{min} {}", noun(min)),
+        Some(max) => format!("{min} to {max} arguments"),
+        None => format!("at least {min} {}", noun(min)),
+    }
+}
+
+fn arity_error(name: &str, min: usize, max: Option<usize>, given: usize) -> Parameters {
+    let verb = if given == 1 { "was" } else { "were" };
+    Identifier(format!(
+        "@{name} takes {} but {given} {verb} given",
+        describe_arity(min, max)
+    ))
+}
+
+/// Gives the arguments of a call to the parameters of an equation. Named
+/// arguments go to the parameter of the same name, the positional ones fill
+/// the others in order, parameters left without a value take their default
+/// and `...args` gets the remaining arguments as a vector. Returns the names
+/// to bind with their values, or `None` when an argument does not equal its
+/// pattern.
+fn bind_arguments(
+    name: &str,
+    owner: &Scope,
+    params: &[Ast],
+    lst: Vec<Parameters>,
+    named: &Named,
+) -> Result<Option<Bound>, Parameters> {
+    let variadic = matches!(
+        params.last(),
+        Some(Ast::Node {
+            value: Variadic(_),
+            ..
+        })
+    );
+    let fixed = if variadic {
+        params.len() - 1
+    } else {
+        params.len()
+    };
+    let required = params[..fixed]
+        .iter()
+        .filter(|p| {
+            !matches!(
+                p,
+                Ast::Node {
+                    value: Parameters::Assign,
+                    ..
+                }
+            )
+        })
+        .count();
+    let max = if variadic { None } else { Some(fixed) };
+    let given = lst.len() + named.len();
+
+    let mut slots: Vec<Option<Parameters>> = vec![None; fixed];
+    for (arg, value) in named {
+        let position = params[..fixed]
+            .iter()
+            .position(|p| p.parameter_name() == Some(arg.as_str()));
+        match position {
+            None => return Err(Identifier(format!("@{name} has no parameter called {arg}"))),
+            Some(i) if slots[i].is_some() => {
+                return Err(Identifier(format!("@{name} got several values for {arg}")))
+            }
+            Some(i) => slots[i] = Some(value.clone()),
+        }
+    }
+    let mut positional = lst.into_iter();
+    for slot in slots.iter_mut().filter(|slot| slot.is_none()) {
+        *slot = positional.next();
+    }
+    let rest: Vec<Parameters> = positional.collect();
+    if !rest.is_empty() && !variadic {
+        return Err(arity_error(name, required, max, given));
+    }
+
+    let mut names = Vec::new();
+    let mut args = Vec::new();
+    for (param, slot) in params.iter().zip(slots) {
+        let value = match (param, slot) {
+            (_, Some(value)) => value,
+            (
+                Ast::Node {
+                    value: Parameters::Assign,
+                    right: default,
+                    ..
+                },
+                None,
+            ) => match invoke(owner, &names, args.clone(), default, HashMap::new()) {
+                error if error.is_error() => return Err(error),
+                value => value,
+            },
+            (_, None) if given < required => return Err(arity_error(name, required, max, given)),
+            (_, None) => {
+                let missing = param
+                    .parameter_name()
+                    .map_or(param.to_string(), str::to_string);
+                return Err(Identifier(format!(
+                    "@{name} is missing the argument {missing}"
+                )));
+            }
+        };
+        match param.parameter_name() {
+            Some(s) => {
+                names.push(s.to_string());
+                args.push(value);
+            }
+            None => match invoke(owner, &[], vec![], param, HashMap::new()) {
+                error if error.is_error() => return Err(error),
+                pattern => {
+                    if equal(pattern, value, None) != Bool(true) {
+                        return Ok(None);
+                    }
+                }
+            },
+        }
+    }
+    if let Some(Ast::Node {
+        value: Variadic(s), ..
+    }) = params.last()
+    {
+        names.push(s.clone());
+        args.push(InterpreterVector(Box::from(rest)));
+    }
+    Ok(Some((names, args)))
+}
+
+/// Calls the first equation of a user function whose patterns and guard match
+/// the arguments, in a child of the scope `owner` it was defined in.
+fn call_equations(
+    name: &str,
+    owner: &Scope,
+    equations: &[Equation],
+    lst: Vec<Parameters>,
+    named: &Named,
+) -> Parameters {
+    let mut failure = None;
+    for equation in equations {
+        let (names, args) = match bind_arguments(name, owner, &equation.params, lst.clone(), named)
+        {
+            Ok(Some(bound)) => bound,
+            Ok(None) => continue,
+            Err(error) => {
+                failure.get_or_insert(error);
+                continue;
+            }
+        };
+        if equation.guard != Ast::Nil {
+            match invoke(owner, &names, args.clone(), &equation.guard, HashMap::new()) {
+                Bool(true) => (),
+                Bool(false) => continue,
+                error if error.is_error() => return error,
+                _ => return Identifier(format!("@the guard of {name} must be a boolean")),
+            }
+        }
+        return invoke(owner, &names, args, &equation.body, HashMap::new());
+    }
+    // the arity of a function defined by a single equation is worth reporting
+    match (equations, failure) {
+        ([_], Some(error)) => error,
+        _ => Identifier(format!("@no equation of {name} matches the arguments")),
+    }
+}
+
+/// Calls `f`, a lambda or the name of a function, with `lst`.
+pub fn apply_function(f: &Parameters, lst: Vec<Parameters>, scope: &mut Scope) -> Parameters {
+    match f {
+        Lambda(lambda) => call_lambda(LAMBDA_NAME, lambda, lst, &Vec::new(), scope),
+        Identifier(name) if !f.is_error() => exec(name.clone(), lst, scope),
+        _ => Identifier(format!("@{f} is not a function")),
+    }
+}
+
+/// The number of arguments the built-in functions take, at least and at
+/// most.
+fn builtin_arity(name: &str) -> Option<(usize, Option<usize>)> {
+    let arity = match name {
+        "cos" | "sin" | "tan" | "cosh" | "sinh" | "tanh" | "acos" | "asin" | "atan" => (1, Some(2)),
+        "exp" | "ln" | "log" | "sqrt" | "round" => (1, Some(2)),
+        "fact" | "factorial" | "abs" | "re" | "im" | "arg" | "conj" | "hex" | "bin" | "oct" => {
+            (1, Some(1))
+        }
+        "ceil" | "floor" | "norm" | "transpose_vector" | "transpose" | "det" | "invert" => {
+            (1, Some(1))
+        }
+        "polar" => (2, Some(3)),
+        "divmod" | "map" | "filter" | "reduce" | "apply" | "index" => (2, Some(2)),
+        "fold" | "slice" => (3, Some(3)),
+        "range" => (1, Some(3)),
+        "plot" | "termplot" => (0, Some(8)),
+        "diff" => (0, Some(2)),
+        "zip" => (0, None),
+        _ => return None,
+    };
+    Some(arity)
+}
+
+/// Whether `name` is a built-in function, which cannot be redefined since
+/// the built-in would still be the one called.
+pub fn is_builtin(name: &str) -> bool {
+    builtin_arity(name).is_some()
+}
+
+/// Calls `s` with arguments given by name as well, only user functions and
+/// lambdas accept them.
+pub fn exec_named(s: String, lst: Vec<Parameters>, named: Named, scope: &mut Scope) -> Parameters {
+    if named.is_empty() {
+        return exec(s, lst, scope);
+    }
+    if builtin_arity(&s).is_some() {
+        return Identifier(format!("@{s} does not take named arguments"));
+    }
+    call_function(&s, lst, named, scope)
+}
+
+pub fn exec(s: String, lst: Vec<Parameters>, scope: &mut Scope) -> Parameters {
+    if let Some((min, max)) = builtin_arity(&s) {
+        if lst.len() < min || max.is_some_and(|max| lst.len() > max) {
+            return arity_error(&s, min, max, lst.len());
+        }
+    }
+    let ram: Ram = Some(&mut *scope.ram);
+    let functions: Functions = Some(&mut *scope.functions);
     match s.as_str() {
         "cos" => cos(&lst, &ram),
         "sin" => sin(&lst, &ram),
@@ -39,6 +394,15 @@ pub fn exec(s: String, lst: Vec<Parameters>, ram: Ram, functions: Functions) ->
         "fact" => factorial(&lst, &ram),
         "factorial" => factorial(&lst, &ram),
         "abs" => abs(&lst, &ram),
+        "re" => complex_part(&lst, &ram, |c| Float(c.re)),
+        "im" => complex_part(&lst, &ram, |c| Float(c.im)),
+        "arg" => complex_part(&lst, &ram, |c| Float(c.arg())),
+        "conj" => complex_part(&lst, &ram, |c| from_complex(c.conj())),
+        "polar" => polar(&lst, &ram),
+        "divmod" => divmod(&lst, &ram),
+        "hex" => radix(&lst, &ram, RadixMode::Hexadecimal),
+        "bin" => radix(&lst, &ram, RadixMode::Binary),
+        "oct" => radix(&lst, &ram, RadixMode::Octal),
         "ceil" => ceil(&lst, &ram),
         "floor" => floor(&lst, &ram),
         "round" => round(&lst, &ram),
@@ -49,53 +413,36 @@ pub fn exec(s: String, lst: Vec<Parameters>, ram: Ram, functions: Functions) ->
         "invert" => inverse_matrix(&lst, &ram),
         "plot" => plot_fn(&lst, &ram, &functions, false),
         "termplot" => plot_fn(&lst, &ram, &functions, true),
-        "diff" => diff(&lst, &ram, &functions),
-        s => {
-            let mut sram: HashMap<String, Parameters> = HashMap::new();
-            sram.insert("pi".to_string(), Float(PI));
-            sram.insert("e".to_string(), Float(E));
-            match functions.cloned() {
-                None => Identifier("@This function is unknown".to_string()),
-                Some(mut f) => {
-                    let (vec, ast): (Vec<Ast>, Ast) = match f.get(s) {
-                        None => {
-                            return Identifier("@This function is unknown".to_string());
-                        }
-                        Some((a, b)) => (a.clone(), b.clone()),
-                    };
-
-                    let mut names = Vec::new();
-                    for v in vec {
-                        match v {
-                            Ast::Nil => (),
-                            Ast::Call { .. } => (),
-                            Ast::Node {
-                                value: v,
-                                left: _l,
-                                right: _r,
-                            } => match v {
-                                Identifier(s) => names.push(s.clone()),
-                                _ => (),
-                            },
-                        }
-                    }
-                    names
-                        .iter()
-                        .zip(lst)
-                        .filter(|(name, param)| match param {
-                            Parameters::Identifier(s) => s.as_str() != name.as_str(),
-                            _ => true,
-                        })
-                        .for_each(|(name, param)| {
-                            sram.insert(name.to_string(), param);
-                        });
-                    interpret(&ast, &mut sram, &mut f)
-                }
+        "diff" => match lst.get(1) {
+            // f'(3), the derivative at a point
+            Some(x @ (Int(_) | Big(_) | Float(_) | Rational(_) | Complex(_))) => {
+                let x = x.clone();
+                let derivative = diff(&lst, &ram, &functions);
+                derivative_at(derivative, &x, scope)
             }
-        }
+            _ => diff(&lst, &ram, &functions),
+        },
+        "map" => map(&lst, scope),
+        "filter" => filter(&lst, scope),
+        "reduce" => reduce(&lst, scope),
+        "fold" => fold(&lst, scope),
+        "zip" => zip(&lst),
+        "apply" => apply(&lst, scope),
+        "index" => index(&lst),
+        "slice" => slice(&lst),
+        "range" => range(&lst),
+        s => call_function(s, lst, Vec::new(), scope),
     }
 }
 
+/// `p` with a float as its first argument, the functions on floats take big
+/// integers as floats.
+fn float_argument(p: &[Parameters], f: f64) -> Vec<Parameters> {
+    let mut p = p.to_vec();
+    p[0] = Float(f);
+    p
+}
+
 pub fn cos(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
     if p.len() < 1 {
         return Null;
@@ -111,6 +458,7 @@ pub fn cos(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
     }
 
     match p.get(0).unwrap() {
+        Big(b) => cos(&float_argument(p, big_to_f64(b)), ram),
         Int(i) => {
             let fs: f64 = if degrees {
                 ((*i).clone() as f64) * (PI / 180.0)
@@ -166,6 +514,7 @@ pub fn cos(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
             });
             InterpreterVector(Box::from(res))
         }
+        Complex(c) => from_complex(if degrees { c * (PI / 180.0) } else { *c }.cos()),
         Identifier(s) => match ram {
             None => Call("cos".to_string(), Box::from(Identifier(s.clone()))),
             Some(ref t) => match t.get(s.as_str()) {
@@ -198,6 +547,7 @@ pub fn sin(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
     }
 
     match p.get(0).unwrap() {
+        Big(b) => sin(&float_argument(p, big_to_f64(b)), ram),
         Int(i) => {
             let fs: f64 = if degrees {
                 ((*i).clone() as f64) * (PI / 180.0)
@@ -253,6 +603,7 @@ pub fn sin(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
             });
             InterpreterVector(Box::from(res))
         }
+        Complex(c) => from_complex(if degrees { c * (PI / 180.0) } else { *c }.sin()),
         Identifier(s) => match ram {
             None => Call("sin".to_string(), Box::from(Identifier(s.clone()))),
             Some(ref t) => match t.get(s.as_str()) {
@@ -285,6 +636,7 @@ pub fn tan(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
     }
 
     match p.get(0).unwrap() {
+        Big(b) => tan(&float_argument(p, big_to_f64(b)), ram),
         Int(i) => {
             let fs: f64 = if degrees {
                 ((*i).clone() as f64) * (PI / 180.0)
@@ -341,6 +693,7 @@ pub fn tan(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
             });
             InterpreterVector(Box::from(res))
         }
+        Complex(c) => from_complex(if degrees { c * (PI / 180.0) } else { *c }.tan()),
         Identifier(s) => match ram {
             None => Call("tan".to_string(), Box::from(Identifier(s.clone()))),
             Some(ref t) => match t.get(s.as_str()) {
@@ -373,6 +726,7 @@ pub fn cosh(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
     }
 
     match p.get(0).unwrap() {
+        Big(b) => cosh(&float_argument(p, big_to_f64(b)), ram),
         Int(i) => {
             let fs: f64 = if degrees {
                 ((*i).clone() as f64) * (PI / 180.0)
@@ -429,6 +783,7 @@ pub fn cosh(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
             });
             InterpreterVector(Box::from(res))
         }
+        Complex(c) => from_complex(if degrees { c * (PI / 180.0) } else { *c }.cosh()),
         Identifier(s) => match ram {
             None => Call("cosh".to_string(), Box::from(Identifier(s.clone()))),
             Some(ref t) => match t.get(s.as_str()) {
@@ -461,6 +816,7 @@ pub fn sinh(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
     }
 
     match p.get(0).unwrap() {
+        Big(b) => sinh(&float_argument(p, big_to_f64(b)), ram),
         Int(i) => {
             let fs: f64 = if degrees {
                 ((*i).clone() as f64) * (PI / 180.0)
@@ -517,6 +873,7 @@ pub fn sinh(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
             });
             InterpreterVector(Box::from(res))
         }
+        Complex(c) => from_complex(if degrees { c * (PI / 180.0) } else { *c }.sinh()),
         Identifier(s) => match ram {
             None => Call("sinh".to_string(), Box::from(Identifier(s.clone()))),
             Some(ref t) => match t.get(s.as_str()) {
@@ -549,6 +906,7 @@ pub fn tanh(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
     }
 
     match p.get(0).unwrap() {
+        Big(b) => tanh(&float_argument(p, big_to_f64(b)), ram),
         Int(i) => {
             let fs: f64 = if degrees {
                 ((*i).clone() as f64) * (PI / 180.0)
@@ -605,6 +963,7 @@ pub fn tanh(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
             });
             InterpreterVector(Box::from(res))
         }
+        Complex(c) => from_complex(if degrees { c * (PI / 180.0) } else { *c }.tanh()),
         Identifier(s) => match ram {
             None => Call("tanh".to_string(), Box::from(Identifier(s.clone()))),
             Some(ref t) => match t.get(s.as_str()) {
@@ -622,6 +981,19 @@ pub fn tanh(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
     }
 }
 
+/// A real number outside of [-1, 1] as a complex, `asin` and `acos` are
+/// complex there.
+fn outside_unit_interval(p: &Parameters) -> Option<Complex64> {
+    let x = match p {
+        Int(i) => *i as f64,
+        Float(f) => *f,
+        Rational(s) => s.approx(),
+        Big(b) => big_to_f64(b),
+        _ => return None,
+    };
+    (x.abs() > 1.0).then(|| Complex64::new(x, 0.0))
+}
+
 pub fn acos(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
     if p.len() < 1 {
         return Null;
@@ -636,7 +1008,14 @@ pub fn acos(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
         }
     }
 
+    if let Some(c) = outside_unit_interval(&p[0]) {
+        let mut p = p.clone();
+        p[0] = Complex(c);
+        return acos(&p, ram);
+    }
+
     match p.get(0).unwrap() {
+        Big(b) => acos(&float_argument(p, big_to_f64(b)), ram),
         Int(i) => {
             let fs: f64 = (*i) as f64;
             Float(if degrees {
@@ -691,6 +1070,11 @@ pub fn acos(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
             });
             InterpreterVector(Box::from(res))
         }
+        Complex(c) => from_complex(if degrees {
+            c.acos() * (180.0 / PI)
+        } else {
+            c.acos()
+        }),
         Identifier(s) => match ram {
             None => Call("acos".to_string(), Box::from(Identifier(s.clone()))),
             Some(ref t) => match t.get(s.as_str()) {
@@ -722,7 +1106,14 @@ pub fn asin(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
         }
     }
 
+    if let Some(c) = outside_unit_interval(&p[0]) {
+        let mut p = p.clone();
+        p[0] = Complex(c);
+        return asin(&p, ram);
+    }
+
     match p.get(0).unwrap() {
+        Big(b) => asin(&float_argument(p, big_to_f64(b)), ram),
         Int(i) => {
             let fs: f64 = (*i) as f64;
             Float(if degrees {
@@ -778,6 +1169,11 @@ pub fn asin(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
             });
             InterpreterVector(Box::from(res))
         }
+        Complex(c) => from_complex(if degrees {
+            c.asin() * (180.0 / PI)
+        } else {
+            c.asin()
+        }),
         Identifier(s) => match ram {
             None => Call("asin".to_string(), Box::from(Identifier(s.clone()))),
             Some(ref t) => match t.get(s.as_str()) {
@@ -810,6 +1206,7 @@ pub fn atan(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
     }
 
     match p.get(0).unwrap() {
+        Big(b) => atan(&float_argument(p, big_to_f64(b)), ram),
         Int(i) => {
             let fs: f64 = (*i) as f64;
             Float(if degrees {
@@ -865,6 +1262,11 @@ pub fn atan(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
             });
             InterpreterVector(Box::from(res))
         }
+        Complex(c) => from_complex(if degrees {
+            c.atan() * (180.0 / PI)
+        } else {
+            c.atan()
+        }),
         Identifier(s) => match ram {
             None => Call("atan".to_string(), Box::from(Identifier(s.clone()))),
             Some(ref t) => match t.get(s.as_str()) {
@@ -905,6 +1307,7 @@ pub fn exp(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
     }
 
     match p.get(0).unwrap() {
+        Big(b) => exp(&float_argument(p, big_to_f64(b)), ram),
         Int(i) => {
             let fs: f64 = (*i) as f64;
             if plus {
@@ -959,6 +1362,7 @@ pub fn exp(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
             });
             InterpreterVector(Box::from(res))
         }
+        Complex(c) => from_complex(if plus { c.expf(ln) } else { c.exp() }),
         Identifier(s) => match ram {
             None => Call("exp".to_string(), Box::from(Identifier(s.clone()))),
             Some(ref t) => match t.get(s.as_str()) {
@@ -993,6 +1397,12 @@ pub fn ln(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
     }
 
     match p.get(0).unwrap() {
+        Big(b) => ln(&float_argument(p, big_to_f64(b)), ram),
+        Int(i) if *i < 0 && !plus => from_complex(Complex64::new(*i as f64, 0.0).ln()),
+        Float(f) if *f < 0.0 && !plus => from_complex(Complex64::new(*f, 0.0).ln()),
+        Rational(s) if s.approx() < 0.0 && !plus => {
+            from_complex(Complex64::new(s.approx(), 0.0).ln())
+        }
         Int(i) => {
             let fs: f64 = (*i) as f64;
             if plus {
@@ -1048,6 +1458,7 @@ pub fn ln(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
             });
             InterpreterVector(Box::from(res))
         }
+        Complex(c) => from_complex(if plus { c.log(sln) } else { c.ln() }),
         Identifier(s) => match ram {
             None => Call("ln".to_string(), Box::from(Identifier(s.clone()))),
             Some(ref t) => match t.get(s.as_str()) {
@@ -1082,6 +1493,12 @@ pub fn sqrt(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
     }
 
     match p.get(0).unwrap() {
+        Big(b) => sqrt(&float_argument(p, big_to_f64(b)), ram),
+        Int(i) if *i < 0 && !plus => from_complex(Complex64::new(*i as f64, 0.0).sqrt()),
+        Float(f) if *f < 0.0 && !plus => from_complex(Complex64::new(*f, 0.0).sqrt()),
+        Rational(s) if s.approx() < 0.0 && !plus => {
+            from_complex(Complex64::new(s.approx(), 0.0).sqrt())
+        }
         Int(i) => {
             let fs: f64 = (*i) as f64;
             if plus {
@@ -1140,6 +1557,7 @@ pub fn sqrt(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
             });
             InterpreterVector(Box::from(res))
         }
+        Complex(c) => from_complex(if plus { c.powf(1.0 / sln) } else { c.sqrt() }),
         Identifier(s) => match ram {
             None => Call("sqrt".to_string(), Box::from(Identifier(s.clone()))),
             Some(ref t) => match t.get(s.as_str()) {
@@ -1151,14 +1569,8 @@ pub fn sqrt(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
     }
 }
 
-pub fn fact(n: i64) -> i64 {
-    fn aux(n: i64, acc: i64) -> i64 {
-        match n {
-            0 => acc,
-            i => aux(i - 1, i * acc),
-        }
-    }
-    aux(n, 1)
+pub fn fact(n: i64) -> BigInt {
+    (1..=n).fold(BigInt::from(1), |acc, i| acc * i)
 }
 
 pub fn factorial(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
@@ -1167,8 +1579,15 @@ pub fn factorial(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
     }
 
     match p.get(0).unwrap() {
-        Int(i) => Parameters::Int(fact(*i)),
-        Float(f) => Parameters::Int(fact(*f as i64)),
+        Int(i) if *i < 0 => Identifier("@factorial of a negative number".to_string()),
+        Float(f) if *f < 0.0 => Identifier("@factorial of a negative number".to_string()),
+        Int(i) => from_big(fact(*i)),
+        Float(f) => from_big(fact(*f as i64)),
+        Rational(r) => match r.clone().reduce() {
+            r if r.under.is_one() => factorial(&vec![from_big(r.over)], ram),
+            r => Identifier(format!("@factorial of {r} which is not an integer")),
+        },
+        Big(b) => Identifier(format!("@factorial of {b} is too large")),
         Identifier(s) => match ram {
             None => Identifier("This variable is not initialized yet".to_string()),
             Some(ref t) => match t.get(s.as_str()) {
@@ -1186,9 +1605,14 @@ pub fn abs(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
     }
 
     match p.get(0).unwrap() {
-        Int(i) => Parameters::Int(i.abs()),
+        Int(i) => match i.checked_abs() {
+            Some(r) => Parameters::Int(r),
+            None => from_big(BigInt::from(*i).abs()),
+        },
+        Big(i) => Parameters::Big(i.abs()),
         Float(f) => Parameters::Float(f.abs()),
         Rational(s) => Parameters::Rational(s.clone().abs()),
+        Complex(c) => Parameters::Float(c.norm()),
         Identifier(s) => match ram {
             None => Identifier("This variable is not initialized yet".to_string()),
             Some(ref t) => match t.get(s.as_str()) {
@@ -1200,6 +1624,95 @@ pub fn abs(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
     }
 }
 
+/// Applies `f` to the argument seen as a complex number, real numbers having
+/// a null imaginary part.
+pub fn complex_part(p: &[Parameters], ram: &Ram, f: fn(Complex64) -> Parameters) -> Parameters {
+    if p.is_empty() {
+        return Null;
+    }
+
+    match p.first().unwrap() {
+        Complex(c) => f(*c),
+        Int(i) => f(Complex64::new(*i as f64, 0.0)),
+        Float(fs) => f(Complex64::new(*fs, 0.0)),
+        Rational(s) => f(Complex64::new(s.approx(), 0.0)),
+        Big(i) => f(Complex64::new(big_to_f64(i), 0.0)),
+        Identifier(s) => match ram {
+            None => Identifier("This variable is not initialized yet".to_string()),
+            Some(ref t) => match t.get(s.as_str()) {
+                None => Null,
+                Some(t) => complex_part(std::slice::from_ref(t), ram, f),
+            },
+        },
+        _ => Null,
+    }
+}
+
+pub fn divmod(p: &[Parameters], ram: &Ram) -> Parameters {
+    if p.len() < 2 {
+        return Null;
+    }
+    other_divmod(p[0].clone(), p[1].clone(), ram.as_deref())
+}
+
+/// Builds a complex number from its modulus and argument, the argument is in
+/// degrees when a third parameter is given.
+pub fn polar(p: &[Parameters], ram: &Ram) -> Parameters {
+    if p.len() < 2 {
+        return Null;
+    }
+
+    let to_f64 = |param: &Parameters| match param {
+        Int(i) => Some(*i as f64),
+        Float(f) => Some(*f),
+        Rational(s) => Some(s.approx()),
+        Big(i) => Some(big_to_f64(i)),
+        Identifier(s) => match ram {
+            None => None,
+            Some(ref t) => match t.get(s.as_str()) {
+                Some(Int(i)) => Some(*i as f64),
+                Some(Float(f)) => Some(*f),
+                Some(Rational(s)) => Some(s.approx()),
+                _ => None,
+            },
+        },
+        _ => None,
+    };
+
+    match (to_f64(&p[0]), to_f64(&p[1])) {
+        (Some(r), Some(theta)) => {
+            let theta = if p.len() > 2 {
+                theta * PI / 180.0
+            } else {
+                theta
+            };
+            from_complex(Complex64::from_polar(r, theta))
+        }
+        _ => Identifier("@polar expects real numbers".to_string()),
+    }
+}
+
+/// Writes an integer in the given base, regardless of the radix mode.
+pub fn radix(p: &[Parameters], ram: &Ram, mode: RadixMode) -> Parameters {
+    if p.is_empty() {
+        return Null;
+    }
+
+    match p.first().unwrap() {
+        Int(i) => Str(to_radix_string(&BigInt::from(*i), &mode)),
+        Big(i) => Str(to_radix_string(i, &mode)),
+        Rational(s) if s.under.is_one() => Str(to_radix_string(&s.over, &mode)),
+        Identifier(s) => match ram {
+            None => Identifier("This variable is not initialized yet".to_string()),
+            Some(ref t) => match t.get(s.as_str()) {
+                None => Null,
+                Some(t) => radix(std::slice::from_ref(t), ram, mode),
+            },
+        },
+        _ => Identifier("@only integers can be written in another base".to_string()),
+    }
+}
+
 pub fn ceil(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
     if p.len() < 1 {
         return Null;
@@ -1207,6 +1720,7 @@ pub fn ceil(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
 
     match p.get(0).unwrap() {
         Int(i) => Parameters::Float((*i as f64).ceil()),
+        Big(i) => Parameters::Big(i.clone()),
         Float(f) => Parameters::Float(f.ceil()),
         Identifier(s) => match ram {
             None => Identifier("This variable is not initialized yet".to_string()),
@@ -1226,6 +1740,7 @@ pub fn floor(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
 
     match p.get(0).unwrap() {
         Int(i) => Parameters::Float((*i as f64).floor()),
+        Big(i) => Parameters::Big(i.clone()),
         Float(f) => Parameters::Float(f.floor()),
         Identifier(s) => match ram {
             None => Identifier("This variable is not initialized yet".to_string()),
@@ -1276,6 +1791,7 @@ pub fn round(p: &Vec<Parameters>, ram: &Ram) -> Parameters {
                 Float((*f).round())
             }
         }
+        Big(i) => Parameters::Big(i.clone()),
         Rational(s) => {
             if plus {
                 Float((s.clone().approx() * 10.0_f64.powf(sln).round()) / (10.0_f64.powf(sln)))
@@ -1302,6 +1818,7 @@ pub fn norm(p: &Vec<Parameters>, ram: &Ram, function: &Functions) -> Parameters
     match p.get(0).unwrap() {
         Int(i) => Parameters::Int((*i).abs()),
         Float(f) => Parameters::Float((*f).abs()),
+        Big(b) => Parameters::Big(b.abs()),
         InterpreterVector(lst) => {
             let mut sum = Int(0);
 
@@ -1314,6 +1831,7 @@ pub fn norm(p: &Vec<Parameters>, ram: &Ram, function: &Functions) -> Parameters
                 Int(i) => Parameters::Float((i as f64).sqrt()),
                 Float(f) => Parameters::Float(f.sqrt()),
                 Rational(s) => Parameters::Float(s.approx().sqrt()),
+                Big(b) => Parameters::Float(big_to_f64(&b).sqrt()),
                 _ => Float(0.0),
             }
         }
@@ -1560,10 +2078,17 @@ pub fn diff(p: &Vec<Parameters>, ram: &Ram, function: &Functions) -> Parameters
     for (key, ele) in ram.as_deref().unwrap().clone() {
         c.insert(key, ele);
     }
-    let mut s: HashMap<String, (Vec<Ast>, Ast)> = HashMap::new();
+    let mut s: ast::Functions = HashMap::new();
     for (key, ele) in function.as_deref().unwrap().clone() {
         s.insert(key, ele);
     }
+    if let Lambda(lambda) = first_param {
+        c.extend(lambda.captured.clone());
+        s.insert(LAMBDA_NAME.to_string(), vec![lambda_definition(lambda)]);
+        let mut p = p.clone();
+        p[0] = Identifier(LAMBDA_NAME.to_string());
+        return diff(&p, &Some(&mut c), &Some(&mut s));
+    }
     let insert = if second_param {
         p.get(1).unwrap().clone()
     } else {
@@ -1614,7 +2139,7 @@ pub fn diff(p: &Vec<Parameters>, ram: &Ram, function: &Functions) -> Parameters
                 ),
                 Some(&c),
             ),
-            "x" => Identifier("1".to_string()),
+            "x" => Int(1),
             "sqrt" => divide(
                 Int(1),
                 mult(
@@ -1628,8 +2153,7 @@ pub fn diff(p: &Vec<Parameters>, ram: &Ram, function: &Functions) -> Parameters
                 let param = exec(
                     p.to_string(),
                     vec![Identifier("x".to_string())],
-                    Some(&mut c),
-                    Some(&mut s),
+                    &mut Scope::new(&mut c, &mut s),
                 );
                 match param {
                     Identifier(_) => Int(1),
@@ -1660,13 +2184,13 @@ pub fn diff(p: &Vec<Parameters>, ram: &Ram, function: &Functions) -> Parameters
                     Div(x, y) => Div(
                         Box::from(other_add(
                             mult(
-                                *x.clone(),
-                                diff(&vec![*y.clone()], &Some(&mut c), &Some(&mut s)),
+                                *y.clone(),
+                                diff(&vec![*x.clone()], &Some(&mut c), &Some(&mut s)),
                                 Some(&c),
                             ),
                             mult(
-                                mult(Int(-1), *y.clone(), Some(&c)),
-                                diff(&vec![*x.clone()], &Some(&mut c), &Some(&mut s)),
+                                mult(Int(-1), *x.clone(), Some(&c)),
+                                diff(&vec![*y.clone()], &Some(&mut c), &Some(&mut s)),
                                 Some(&c),
                             ),
                             Some(&c),
@@ -1709,16 +2233,17 @@ pub fn diff(p: &Vec<Parameters>, ram: &Ram, function: &Functions) -> Parameters
             ),
             Some(&c),
         ),
+        // (u/v)' = (v u' - u v') / v²
         Div(x, y) => Div(
             Box::from(other_add(
                 mult(
-                    *x.clone(),
-                    diff(&vec![*y.clone()], &Some(&mut c), &Some(&mut s)),
+                    *y.clone(),
+                    diff(&vec![*x.clone()], &Some(&mut c), &Some(&mut s)),
                     Some(&c),
                 ),
                 mult(
-                    Mul(Box::from(Int(-1)), y.clone()),
-                    diff(&vec![*x.clone()], &Some(&mut c), &Some(&mut s)),
+                    mult(Int(-1), *x.clone(), Some(&c)),
+                    diff(&vec![*y.clone()], &Some(&mut c), &Some(&mut s)),
                     Some(&c),
                 ),
                 Some(&c),
@@ -1739,6 +2264,39 @@ pub fn diff(p: &Vec<Parameters>, ram: &Ram, function: &Functions) -> Parameters
     }
 }
 
+/// Evaluates a derivative given by `diff`, where `x` stands for the point
+/// `x` and the calls of built-in functions are yet to be made.
+fn derivative_at(derivative: Parameters, x: &Parameters, scope: &mut Scope) -> Parameters {
+    match derivative {
+        Var(coefficient, power, name) if name == "x" => mult(
+            *coefficient,
+            expo(x.clone(), Int(power), Some(scope.ram)),
+            Some(scope.ram),
+        ),
+        Identifier(name) if name == "x" => x.clone(),
+        Plus(a, b) => {
+            let a = derivative_at(*a, x, scope);
+            let b = derivative_at(*b, x, scope);
+            other_add(a, b, Some(scope.ram))
+        }
+        Mul(a, b) => {
+            let a = derivative_at(*a, x, scope);
+            let b = derivative_at(*b, x, scope);
+            mult(a, b, Some(scope.ram))
+        }
+        Div(a, b) => {
+            let a = derivative_at(*a, x, scope);
+            let b = derivative_at(*b, x, scope);
+            divide(a, b, Some(scope.ram))
+        }
+        Call(name, arg) => {
+            let arg = derivative_at(*arg, x, scope);
+            exec(name, vec![arg], scope)
+        }
+        p => p,
+    }
+}
+
 pub fn plot_fn(
     p: &Vec<Parameters>,
     ram: &Ram,
@@ -1763,7 +2321,12 @@ pub fn plot_fn(
     let mut fun: bool = true;
     let mut first_vector = None;
     let mut second_vector = None;
+    let mut lambda = None;
     match fs {
+        Lambda(l) => {
+            fd = LAMBDA_NAME.to_string();
+            lambda = Some(l);
+        }
         InterpreterVector(vec) => {
             fun = false;
             first_vector = Some(&**vec)
@@ -2019,17 +2582,12 @@ pub fn plot_fn(
     let mut x = Vec::new();
     let mut y = Vec::new();
     if fun {
-        let (mut vec, mut ast): (Vec<Ast>, Ast) = (Vec::new(), Ast::Nil);
-        match functions {
-            None => (),
-            Some(ref s) => {
-                if s.contains_key(&fd) {
-                    (vec, ast) = s.get(&fd).unwrap().clone();
-                }
-            }
-        }
-
+        let mut sfunctions: ast::Functions = functions.as_deref().cloned().unwrap_or_default();
         let mut sram: HashMap<String, Parameters> = HashMap::new();
+        if let Some(l) = lambda {
+            sfunctions.insert(LAMBDA_NAME.to_string(), vec![lambda_definition(l)]);
+            sram.extend(l.captured.clone());
+        }
         sram.insert("pi".to_string(), Float(PI));
         sram.insert("e".to_string(), Float(E));
         while start <= end {
@@ -2043,28 +2601,15 @@ pub fn plot_fn(
                     _ => f64::NAN,
                 });
             } else {
-                let mut names = Vec::new();
-                for v in vec.clone() {
-                    match v {
-                        Ast::Nil => (),
-                        Ast::Call { .. } => (),
-                        Ast::Node {
-                            value: v,
-                            left: _l,
-                            right: _r,
-                        } => match v {
-                            Identifier(s) => names.push(s.clone()),
-                            _ => (),
-                        },
-                    }
-                }
-                names
-                    .iter()
-                    .zip(vec![Float(start)])
-                    .for_each(|(name, param)| {
-                        sram.insert(name.to_string(), param.clone());
-                    });
-                y.push(match interpret(&ast, &mut sram, &mut HashMap::new()) {
+                let call = Ast::Call {
+                    name: fd.clone(),
+                    lst: vec![Ast::Node {
+                        value: Float(start),
+                        left: Box::from(Ast::Nil),
+                        right: Box::from(Ast::Nil),
+                    }],
+                };
+                y.push(match interpret(&call, &mut sram, &mut sfunctions) {
                     Float(p) => p,
                     Int(i) => i as f64,
                     Rational(s) => s.approx(),
@@ -2139,3 +2684,216 @@ pub fn plot_fn(
     }
     Null
 }
+
+/// `[f(x) for x in v]`
+pub fn map(p: &[Parameters], scope: &mut Scope) -> Parameters {
+    let (f, v) = match p {
+        [f, InterpreterVector(v)] => (f, v),
+        _ => return Identifier("@usage: map(function, vector)".to_string()),
+    };
+    let mut result = Vec::new();
+    for x in v.iter() {
+        let y = apply_function(f, vec![x.clone()], scope);
+        if y.is_error() {
+            return y;
+        }
+        result.push(y);
+    }
+    InterpreterVector(Box::from(result))
+}
+
+/// The elements of `v` for which `f` is true.
+pub fn filter(p: &[Parameters], scope: &mut Scope) -> Parameters {
+    let (f, v) = match p {
+        [f, InterpreterVector(v)] => (f, v),
+        _ => return Identifier("@usage: filter(function, vector)".to_string()),
+    };
+    let mut result = Vec::new();
+    for x in v.iter() {
+        match apply_function(f, vec![x.clone()], scope) {
+            Bool(true) => result.push(x.clone()),
+            Bool(false) => (),
+            error if error.is_error() => return error,
+            _ => return Identifier("@the function of filter must return a boolean".to_string()),
+        }
+    }
+    InterpreterVector(Box::from(result))
+}
+
+fn fold_vector(
+    f: &Parameters,
+    init: Parameters,
+    v: &[Parameters],
+    scope: &mut Scope,
+) -> Parameters {
+    let mut acc = init;
+    for x in v {
+        acc = apply_function(f, vec![acc, x.clone()], scope);
+        if acc.is_error() {
+            return acc;
+        }
+    }
+    acc
+}
+
+/// `f(...f(f(v1, v2), v3)..., vn)`
+pub fn reduce(p: &[Parameters], scope: &mut Scope) -> Parameters {
+    match p {
+        [f, InterpreterVector(v)] => match v.split_first() {
+            None => Identifier("@cannot reduce an empty vector".to_string()),
+            Some((first, rest)) => fold_vector(f, first.clone(), rest, scope),
+        },
+        _ => Identifier("@usage: reduce(function, vector)".to_string()),
+    }
+}
+
+/// Like `reduce` but starts from `init`, `f(...f(f(init, v1), v2)..., vn)`
+pub fn fold(p: &[Parameters], scope: &mut Scope) -> Parameters {
+    match p {
+        [f, InterpreterVector(v), init] => fold_vector(f, init.clone(), v, scope),
+        _ => Identifier("@usage: fold(function, vector, initial value)".to_string()),
+    }
+}
+
+/// `[[a1, b1, ...], [a2, b2, ...], ...]`, as long as the shortest vector.
+pub fn zip(p: &[Parameters]) -> Parameters {
+    let mut vectors = Vec::new();
+    for x in p {
+        match x {
+            InterpreterVector(v) => vectors.push(v),
+            _ => return Identifier("@usage: zip(vector, vector, ...)".to_string()),
+        }
+    }
+    let length = vectors.iter().map(|v| v.len()).min().unwrap_or(0);
+    let result: Vec<Parameters> = (0..length)
+        .map(|i| {
+            let row: Vec<Parameters> = vectors.iter().map(|v| v[i].clone()).collect();
+            InterpreterVector(Box::from(row))
+        })
+        .collect();
+    InterpreterVector(Box::from(result))
+}
+
+/// Calls `f` with the elements of `v` as arguments.
+pub fn apply(p: &[Parameters], scope: &mut Scope) -> Parameters {
+    match p {
+        [f, InterpreterVector(v)] => apply_function(f, v.to_vec(), scope),
+        _ => Identifier("@usage: apply(function, vector)".to_string()),
+    }
+}
+
+/// Largest vector `range` builds.
+const MAX_RANGE: usize = 1 << 20;
+
+/// The integer an index stands for, as a big integer so that `v[2^70]` is
+/// out of bounds rather than not an integer.
+fn to_index(p: &Parameters) -> Result<BigInt, Parameters> {
+    match p {
+        Int(i) => Ok(BigInt::from(*i)),
+        Big(i) => Ok(i.clone()),
+        Rational(r) => match r.clone().reduce() {
+            r if r.under.is_one() => Ok(r.over),
+            _ => Err(Identifier(format!("@indices must be integers, not {p}"))),
+        },
+        _ => Err(Identifier(format!("@indices must be integers, not {p}"))),
+    }
+}
+
+/// `v[i]`, negative indices count from the end of the vector.
+pub fn index(p: &[Parameters]) -> Parameters {
+    let (v, i) = match p {
+        [InterpreterVector(v), i] => (v, i),
+        [v, _] => return Identifier(format!("@only vectors can be indexed, not {v}")),
+        _ => return Identifier("@usage: index(vector, index)".to_string()),
+    };
+    let i = match to_index(i) {
+        Ok(i) => i,
+        Err(e) => return e,
+    };
+    let position = if i.is_negative() {
+        &i + v.len()
+    } else {
+        i.clone()
+    };
+    match position.to_usize().and_then(|x| v.get(x)) {
+        Some(x) => x.clone(),
+        None => Identifier(format!(
+            "@index {i} is out of bounds for a vector of length {}",
+            v.len()
+        )),
+    }
+}
+
+/// `v[i:j]`, the elements from `i` included to `j` excluded. Bounds may be
+/// omitted or negative, like indices they must lie within the vector, its
+/// length included.
+pub fn slice(p: &[Parameters]) -> Parameters {
+    let (v, start, end) = match p {
+        [InterpreterVector(v), start, end] => (v, start, end),
+        [v, _, _] => return Identifier(format!("@only vectors can be sliced, not {v}")),
+        _ => return Identifier("@usage: slice(vector, start, end)".to_string()),
+    };
+    let length = v.len();
+    let bound = |p: &Parameters, default: usize| match p {
+        Null => Ok(default),
+        p => {
+            let i = to_index(p)?;
+            let position = if i.is_negative() {
+                &i + length
+            } else {
+                i.clone()
+            };
+            match position.to_usize() {
+                Some(position) if position <= length => Ok(position),
+                _ => Err(Identifier(format!(
+                    "@slice bound {i} is out of bounds for a vector of length {length}"
+                ))),
+            }
+        }
+    };
+    let (start, end) = match (bound(start, 0), bound(end, length)) {
+        (Ok(start), Ok(end)) => (start, end),
+        (Err(e), _) | (_, Err(e)) => return e,
+    };
+    let result = if start < end {
+        v[start..end].to_vec()
+    } else {
+        Vec::new()
+    };
+    InterpreterVector(Box::from(result))
+}
+
+/// `range(end)`, `range(start, end)` or `range(start, end, step)`, the end is
+/// excluded like in `start..end`.
+pub fn range(p: &[Parameters]) -> Parameters {
+    let (start, end, step) = match p {
+        [end] => (Int(0), end.clone(), Int(1)),
+        [start, end] => (start.clone(), end.clone(), Int(1)),
+        [start, end, step] => (start.clone(), end.clone(), step.clone()),
+        _ => return Identifier("@usage: range(start, end, step)".to_string()),
+    };
+    let is_number = |p: &Parameters| matches!(p, Int(_) | Big(_) | Rational(_) | Float(_));
+    if !(is_number(&start) && is_number(&end) && is_number(&step)) {
+        return Identifier("@the bounds and step of a range must be real numbers".to_string());
+    }
+    let before_end: fn(Parameters, Parameters, Option<&ast::Ram>) -> Parameters = match (
+        greater(step.clone(), Int(0), None),
+        lesser(step.clone(), Int(0), None),
+    ) {
+        (Bool(true), _) => lesser,
+        (_, Bool(true)) => greater,
+        _ => return Identifier("@the step of a range cannot be zero".to_string()),
+    };
+    let mut result = Vec::new();
+    let mut x = start;
+    while before_end(x.clone(), end.clone(), None) == Bool(true) {
+        if result.len() == MAX_RANGE {
+            return Identifier(format!(
+                "@a range cannot have more than {MAX_RANGE} elements"
+            ));
+        }
+        result.push(x.clone());
+        x = other_add(x, step.clone(), None);
+    }
+    InterpreterVector(Box::from(result))
+}
@@ -1,8 +1,11 @@
 use std::str::FromStr;
 
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+
 use crate::lexing::token::Operator::*;
-use crate::lexing::token::Token;
-use crate::lexing::token::Token::PreAnd;
+use crate::lexing::token::{Span, SpannedToken, Token};
+use crate::parsing::error::{ParseError, ParseErrorKind};
 
 pub fn is_an_allowed_char(character: char) -> bool {
     character.is_alphanumeric()
@@ -22,25 +25,28 @@ pub fn is_an_allowed_char(character: char) -> bool {
         || character == '>'
         || character == '|'
         || character == '&'
+        || character == '~'
+        || character == '%'
+        || character == ';'
+        || character == ':'
+        || character == '\''
         || character == '['
         || character == ']'
+        || character == '{'
+        || character == '}'
         || character == '_'
         || character == '"'
-        || character == ' '
+        || character.is_whitespace()
 }
 
-fn lex_int(
-    current_char: char,
-    chars: &mut Vec<char>,
-    current_pos: usize,
-    len: usize,
-) -> (i64, usize) {
-    let (a, b) = lex_raddix(current_char, chars, current_pos, len);
-    let err = i64::from_str(&a);
-    if err.is_err() {
-        (0, b)
-    } else {
-        (err.unwrap(), b)
+/// Integers which do not fit in an i64 are lexed as big integers.
+fn lex_int(digits: &str) -> Token {
+    match i64::from_str(digits) {
+        Ok(i) => Token::INT(i),
+        Err(_) => match BigInt::from_str(digits) {
+            Ok(i) => Token::BIGINT(i),
+            Err(_) => Token::INT(0),
+        },
     }
 }
 
@@ -85,11 +91,11 @@ fn lex_string(
 }
 
 fn lex_float(
-    whole_side: i64,
+    whole_side: &str,
     chars: &mut Vec<char>,
     mut current_pos: usize,
     len: usize,
-) -> (f64, usize) {
+) -> (String, usize) {
     current_pos += 1;
     let current_char_options = chars.get(current_pos);
     let current_char = match current_char_options {
@@ -97,228 +103,351 @@ fn lex_float(
         None => &'0',
     };
     let (a, b) = lex_raddix(*current_char, chars, current_pos, len);
-    let f = f64::from_str(&*(whole_side.to_string().as_str().to_owned() + "." + a.as_str()));
-    if f.is_err() {
-        return (f64::NAN, b);
+    (whole_side.to_owned() + "." + a.as_str(), b)
+}
+
+/// Lexes the exponent of a number in scientific notation such as `1e-9`, a
+/// lone `e` is not an exponent but a sign without digits (`1e+`) is malformed.
+fn lex_exponent(
+    chars: &mut Vec<char>,
+    current_pos: usize,
+    len: usize,
+) -> Result<Option<(String, usize)>, usize> {
+    if !matches!(chars.get(current_pos), Some('e') | Some('E')) {
+        return Ok(None);
+    }
+    let mut pos = current_pos + 1;
+    let mut exponent = String::new();
+    if let Some(c @ ('+' | '-')) = chars.get(pos) {
+        exponent.push(*c);
+        pos += 1;
+    }
+    match chars.get(pos) {
+        Some(c) if c.is_ascii_digit() => {
+            let (digits, end) = lex_raddix(*c, chars, pos, len);
+            Ok(Some((exponent + &digits, end)))
+        }
+        _ if exponent.is_empty() => Ok(None),
+        _ => Err(pos),
     }
-    (f.unwrap(), b)
 }
 
-pub fn lex(input: String) -> Vec<Token> {
-    let mut vec: Vec<Token> = Vec::new();
+/// Lexes the digits of a `0x`, `0b` or `0o` literal, `_` can be used as a
+/// separator.
+fn lex_radix_literal(
+    radix: u32,
+    chars: &[char],
+    mut current_pos: usize,
+    len: usize,
+) -> Result<(Token, usize), usize> {
+    let start = current_pos;
+    while current_pos < len && (chars[current_pos].is_alphanumeric() || chars[current_pos] == '_') {
+        current_pos += 1;
+    }
+    let digits: String = chars[start..current_pos]
+        .iter()
+        .filter(|c| **c != '_')
+        .collect();
+    match BigInt::parse_bytes(digits.as_bytes(), radix) {
+        Some(i) if !digits.is_empty() => match i.to_i64() {
+            Some(i) => Ok((Token::INT(i), current_pos)),
+            None => Ok((Token::BIGINT(i), current_pos)),
+        },
+        _ => Err(current_pos),
+    }
+}
+
+/// Lexes a decimal number with an optional fractional part and an optional
+/// exponent, or a `0x`, `0b` or `0o` prefixed integer. On error the position
+/// where the literal stops making sense is returned.
+fn lex_number(
+    whole_side: &str,
+    chars: &mut Vec<char>,
+    mut current_pos: usize,
+    len: usize,
+) -> Result<(Token, usize), usize> {
+    let radix = match chars.get(current_pos) {
+        Some('x') | Some('X') => 16,
+        Some('b') | Some('B') => 2,
+        Some('o') | Some('O') => 8,
+        _ => 10,
+    };
+    if whole_side == "0" && radix != 10 {
+        return lex_radix_literal(radix, chars, current_pos + 1, len);
+    }
+    let is_dot = |chars: &Vec<char>, pos: usize| {
+        chars.get(pos) == Some(&'.') && chars.get(pos + 1) != Some(&'.')
+    };
+    let mut literal = whole_side.to_string();
+    let mut float = false;
+    if is_dot(chars, current_pos) {
+        let (a, b) = lex_float(whole_side, chars, current_pos, len);
+        (literal, current_pos, float) = (a, b, true);
+    }
+    if let Some((exponent, b)) = lex_exponent(chars, current_pos, len)? {
+        (literal, current_pos, float) = (literal + "e" + &exponent, b, true);
+    }
+    if is_dot(chars, current_pos) {
+        return Err(current_pos + 1);
+    }
+    if !float {
+        return Ok((lex_int(&literal), current_pos));
+    }
+    match f64::from_str(&literal) {
+        Ok(f) => Ok((Token::FLOAT(f), current_pos)),
+        Err(_) => Err(current_pos),
+    }
+}
+
+/// `3i` or `0.5i`, a number directly followed by an `i` which does not start
+/// a word like in `3in`.
+fn lex_imaginary(token: &Token, chars: &[char], end: usize) -> Option<(Token, usize)> {
+    let value = match token {
+        Token::INT(i) => *i as f64,
+        Token::BIGINT(i) => i.to_f64()?,
+        Token::FLOAT(f) => *f,
+        _ => return None,
+    };
+    let word = chars
+        .get(end + 1)
+        .is_some_and(|c| c.is_alphanumeric() || *c == '_');
+    (chars.get(end) == Some(&'i') && !word).then_some((Token::IMAGINARY(value), end + 1))
+}
+
+/// Whether the last token of `vec` ends right where `position` starts, `!=`
+/// is only an operator when nothing separates its characters.
+fn touches(vec: &[SpannedToken], position: usize) -> bool {
+    vec.last().is_some_and(|t| t.span.end == position)
+}
+
+/// Replaces the last token, which is the first half of a two characters
+/// operator such as `<=` or `&&`, the merged token spans both characters.
+fn merge(vec: &mut Vec<SpannedToken>, span_start: &mut usize, token: Token) -> Token {
+    if let Some(t) = vec.pop() {
+        *span_start = t.span.start;
+    }
+    token
+}
+
+pub fn lex(input: String) -> Result<Vec<SpannedToken>, ParseError> {
+    let mut vec: Vec<SpannedToken> = Vec::new();
 
     let mut current_pos = 0;
 
     let mut chars = input.as_str().chars().collect::<Vec<char>>();
+    let mut offsets = input
+        .as_str()
+        .char_indices()
+        .map(|(i, _)| i)
+        .collect::<Vec<usize>>();
+    offsets.push(input.len());
 
     let mut quote_i = 0;
+    let mut last_quote = Span::default();
 
-    let length = input.len();
-    while current_pos < input.len() {
-        let peeking_char = chars.get(current_pos);
-        let current_character: char;
-        match peeking_char {
-            None => {
+    let length = chars.len();
+    while current_pos < length {
+        let start = current_pos;
+        let mut span_start = offsets[start];
+        let current_character = chars[current_pos].to_ascii_lowercase();
+        if current_character == '#' && quote_i % 2 == 0 {
+            // comments run until the end of the line
+            while current_pos < length && chars[current_pos] != '\n' {
                 current_pos += 1;
-                continue;
             }
-            Some(t) => current_character = t.to_ascii_lowercase(),
+            continue;
         }
         if !is_an_allowed_char(current_character) {
-            current_pos += 1;
-            continue;
+            if quote_i % 2 == 1 {
+                vec.push(SpannedToken::new(
+                    Token::IDENTIFIER(current_character.to_string()),
+                    Span::new(offsets[start], offsets[start + 1]),
+                ));
+                current_pos += 1;
+                continue;
+            }
+            return Err(ParseError::new(
+                ParseErrorKind::UnknownCharacter(current_character),
+                Span::new(offsets[start], offsets[start + 1]),
+            ));
         };
 
-        match current_character {
-            '+' => {
-                vec.push(Token::OPE(PLUS));
-                current_pos += 1
-            }
-            '-' => {
-                vec.push(Token::OPE(MINUS));
-                current_pos += 1
-            }
-            '*' => {
-                vec.push(Token::OPE(MULTIPLICATION));
-                current_pos += 1
-            }
-            '/' => {
-                vec.push(Token::OPE(DIVIDE));
-                current_pos += 1
-            }
-            ')' => {
-                vec.push(Token::RPAR);
-                current_pos += 1
-            }
-            '(' => {
-                vec.push(Token::LPAR);
-                current_pos += 1
-            }
-            '>' => {
-                vec.push(Token::OPE(GreaterThan));
-                current_pos += 1
-            }
-            '<' => {
-                vec.push(Token::OPE(LesserThan));
-                current_pos += 1
-            }
+        let token = match current_character {
+            '+' => Token::OPE(PLUS),
+            '-' => Token::OPE(MINUS),
+            '*' => Token::OPE(MULTIPLICATION),
+            '/' => match vec.last().map(|t| t.token.clone()) {
+                Some(Token::OPE(DIVIDE)) => {
+                    merge(&mut vec, &mut span_start, Token::OPE(FloorDivide))
+                }
+                _ => Token::OPE(DIVIDE),
+            },
+            ')' => Token::RPAR,
+            '(' => Token::LPAR,
+            '>' => match vec.last().map(|t| t.token.clone()) {
+                Some(Token::OPE(GreaterThan)) => {
+                    merge(&mut vec, &mut span_start, Token::OPE(ShiftRight))
+                }
+                Some(Token::OPE(MINUS)) => merge(&mut vec, &mut span_start, Token::ARROW),
+                _ => Token::OPE(GreaterThan),
+            },
+            '<' => match vec.last().map(|t| t.token.clone()) {
+                Some(Token::OPE(LesserThan)) => {
+                    merge(&mut vec, &mut span_start, Token::OPE(ShiftLeft))
+                }
+                _ => Token::OPE(LesserThan),
+            },
+            '~' => Token::OPE(BitNot),
             '"' => {
-                vec.push(Token::QUOTE);
                 quote_i += 1;
-                current_pos += 1
+                last_quote = Span::new(offsets[start], offsets[start + 1]);
+                Token::QUOTE
             }
-            '=' => match vec.pop() {
-                Some(Token::EQUAL) => {
-                    vec.push(Token::OPE(EQUALITY));
-                    current_pos += 1
-                }
+            '=' => match vec.last().map(|t| t.token.clone()) {
+                Some(Token::EQUAL) => merge(&mut vec, &mut span_start, Token::OPE(EQUALITY)),
                 Some(Token::OPE(LesserThan)) => {
-                    vec.push(Token::OPE(LesserOrEqual));
-                    current_pos += 1;
+                    merge(&mut vec, &mut span_start, Token::OPE(LesserOrEqual))
                 }
                 Some(Token::OPE(GreaterThan)) => {
-                    vec.push(Token::OPE(GreaterOrEqual));
-                    current_pos += 1;
-                }
-                Some(p) => {
-                    vec.push(p);
-                    vec.push(Token::EQUAL);
-                    current_pos += 1
+                    merge(&mut vec, &mut span_start, Token::OPE(GreaterOrEqual))
                 }
-                None => {
-                    vec.push(Token::EQUAL);
-                    current_pos += 1
+                Some(Token::OPE(NOT)) if touches(&vec, offsets[start]) => {
+                    merge(&mut vec, &mut span_start, Token::OPE(NotEqual))
                 }
+                // `50% == x` compares rather than assigns
+                _ if chars.get(current_pos + 1) == Some(&'=') => Token::EQUAL,
+                Some(Token::OPE(
+                    op @ (PLUS | MINUS | MULTIPLICATION | DIVIDE | FloorDivide | EXPO),
+                )) => merge(&mut vec, &mut span_start, Token::COMPOUND(op)),
+                Some(Token::PERCENT) => merge(&mut vec, &mut span_start, Token::COMPOUND(Mod)),
+                _ => Token::EQUAL,
             },
-            '&' => match vec.pop() {
-                Some(Token::PreAnd) => {
-                    vec.push(Token::OPE(And));
-                    current_pos += 1;
-                }
-                Some(p) => {
-                    vec.push(p);
-                    vec.push(Token::PreAnd);
-                    current_pos += 1;
-                }
-                _ => {
-                    vec.push(Token::PreAnd);
-                    current_pos += 1;
-                }
+            '&' => match vec.last().map(|t| t.token.clone()) {
+                Some(Token::OPE(BitAnd)) => merge(&mut vec, &mut span_start, Token::OPE(And)),
+                _ => Token::OPE(BitAnd),
             },
-            '|' => match vec.pop() {
-                Some(Token::PreOr) => {
-                    vec.push(Token::OPE(Or));
-                    current_pos += 1;
-                }
-                Some(p) => {
-                    vec.push(p);
-                    vec.push(Token::PreOr);
-                    current_pos += 1;
-                }
-                _ => {
-                    vec.push(Token::PreOr);
-                    current_pos += 1;
-                }
+            '|' => match vec.last().map(|t| t.token.clone()) {
+                Some(Token::OPE(BitOr)) => merge(&mut vec, &mut span_start, Token::OPE(Or)),
+                _ => Token::OPE(BitOr),
             },
-            '^' => {
-                vec.push(Token::OPE(EXPO));
-                current_pos += 1
-            }
-            ',' => {
-                vec.push(Token::COMMA);
-                current_pos += 1
+            '^' => Token::OPE(EXPO),
+            ',' => Token::COMMA,
+            '!' => Token::OPE(NOT),
+            '%' => Token::PERCENT,
+            '\'' => Token::PRIME,
+            ']' => Token::RBRACKET,
+            '[' => Token::LBRACKET,
+            '}' => Token::RBRACE,
+            '{' => Token::LBRACE,
+            ';' => Token::SEMICOLON,
+            ':' => Token::COLON,
+            '.' if chars.get(current_pos + 1..current_pos + 3) == Some(&['.', '.']) => {
+                current_pos += 3;
+                Token::ELLIPSIS
             }
-            '!' => {
-                vec.push(Token::OPE(NOT));
-                current_pos += 1
+            '.' if chars.get(current_pos + 1) == Some(&'.') => {
+                current_pos += 2;
+                Token::RANGE
             }
-            ']' => {
-                vec.push(Token::RBRACKET);
-                current_pos += 1
-            }
-            '[' => {
-                vec.push(Token::LBRACKET);
-                current_pos += 1
-            }
-            ' ' => {
+            '\n' if quote_i % 2 == 0 => Token::SEMICOLON,
+            ch if ch.is_whitespace() => {
+                current_pos += 1;
                 if quote_i % 2 == 1 {
-                    vec.push(Token::WHITESPACE);
+                    Token::WHITESPACE
+                } else {
+                    continue;
                 }
-                current_pos += 1
             }
             ch => {
-                if ch.is_numeric() {
-                    let (a, b) = lex_int(current_character, &mut chars, current_pos, length);
-                    current_pos = b;
-                    let cha = chars.get(current_pos);
-                    match cha {
-                        Some(char) => {
-                            if *char == '.' {
-                                let (a1, b1) = lex_float(a, &mut chars, current_pos, length);
-                                current_pos = b1;
-                                vec.push(Token::FLOAT(a1))
-                            } else {
-                                vec.push(Token::INT(a));
-                                current_pos = b;
-                            }
+                if ch.is_ascii_digit() || ch == '.' {
+                    let (a, b) = lex_raddix(current_character, &mut chars, current_pos, length);
+                    let whole_side = if a.is_empty() { "0".to_string() } else { a };
+                    let number =
+                        lex_number(&whole_side, &mut chars, b, length).map(|(token, end)| {
+                            lex_imaginary(&token, &chars, end).unwrap_or((token, end))
+                        });
+                    match number {
+                        // 1e400 does not fit in a float
+                        Ok((Token::FLOAT(f) | Token::IMAGINARY(f), end)) if !f.is_finite() => {
+                            return Err(ParseError::new(
+                                ParseErrorKind::NumberOutOfRange,
+                                Span::new(offsets[start], offsets[end.min(length)]),
+                            ))
                         }
-                        None => {
-                            vec.push(Token::INT(a));
-                            current_pos = b;
+                        Ok((token, end)) => {
+                            current_pos = end;
+                            token
+                        }
+                        Err(end) => {
+                            return Err(ParseError::new(
+                                ParseErrorKind::MalformedNumber,
+                                Span::new(offsets[start], offsets[end.min(length)]),
+                            ))
                         }
                     }
-                }
-                if ch.is_alphabetic() || ch == '_' {
+                } else if ch.is_alphabetic() || ch == '_' {
                     let (a, b) = lex_string(current_character, &mut chars, current_pos, length);
                     current_pos = b;
-                    if &a == "false" {
-                        vec.push(Token::BOOL(false))
-                    } else if &a == "true" {
-                        vec.push(Token::BOOL(true))
-                    } else if &a == "or" {
-                        vec.push(Token::OPE(Or))
-                    } else if &a == "and" {
-                        vec.push(Token::OPE(And))
-                    } else if &a == "geq" {
-                        vec.push(Token::OPE(GreaterOrEqual))
-                    } else if &a == "leq" {
-                        vec.push(Token::OPE(LesserOrEqual))
-                    } else if &a == "lt" {
-                        vec.push(Token::OPE(LesserThan))
-                    } else if &a == "gt" {
-                        vec.push(Token::OPE(GreaterThan))
-                    } else if &a == "eq" {
-                        vec.push(Token::OPE(EQUALITY))
-                    } else {
-                        vec.push(Token::IDENTIFIER(a))
+                    match a.as_str() {
+                        "false" => Token::BOOL(false),
+                        "true" => Token::BOOL(true),
+                        "or" => Token::OPE(Or),
+                        "and" => Token::OPE(And),
+                        "xor" => Token::OPE(Xor),
+                        "mod" => Token::OPE(Mod),
+                        "if" => Token::IF,
+                        "then" => Token::THEN,
+                        "else" => Token::ELSE,
+                        "del" => Token::DEL,
+                        "memo" => Token::MEMO,
+                        "for" => Token::FOR,
+                        "in" => Token::IN,
+                        "while" => Token::WHILE,
+                        "when" => Token::WHEN,
+                        "geq" => Token::OPE(GreaterOrEqual),
+                        "leq" => Token::OPE(LesserOrEqual),
+                        "lt" => Token::OPE(LesserThan),
+                        "gt" => Token::OPE(GreaterThan),
+                        "eq" => Token::OPE(EQUALITY),
+                        _ => Token::IDENTIFIER(a),
                     }
-                }
-                if ch == '.' {
-                    let (a, b) = lex_float(0, &mut chars, current_pos, length);
-                    current_pos = b;
-                    vec.push(Token::FLOAT(a))
+                } else {
+                    return Err(ParseError::new(
+                        ParseErrorKind::UnknownCharacter(ch),
+                        Span::new(offsets[start], offsets[start + 1]),
+                    ));
                 }
             }
+        };
+        if current_pos == start {
+            current_pos += 1;
         }
+        vec.push(SpannedToken::new(
+            token,
+            Span::new(span_start, offsets[current_pos]),
+        ));
+    }
+
+    if quote_i % 2 == 1 {
+        return Err(ParseError::new(ParseErrorKind::Unclosed('"'), last_quote));
     }
-    let mut result = Vec::new();
-    vec.iter()
-        .filter(|x| x != &&Token::PreOr && x != &&PreAnd)
-        .for_each(|x1| result.push(x1.clone()));
-    result
+
+    Ok(vec)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::lexing::lexer::lex;
     use crate::lexing::token::Operator::*;
+    use crate::lexing::token::Span;
     use crate::lexing::token::Token::*;
+    use crate::parsing::error::{ParseError, ParseErrorKind};
 
     #[test]
     fn lex_plus() {
         let mut expected = Vec::new();
         expected.push(OPE(PLUS));
-        let result = lex("+".to_string());
+        let result = lex("+".to_string()).unwrap();
         assert_eq!(result, expected)
     }
 
@@ -326,7 +455,7 @@ mod tests {
     fn lex_minus() {
         let mut expected = Vec::new();
         expected.push(OPE(MINUS));
-        let result = lex("-".to_string());
+        let result = lex("-".to_string()).unwrap();
         assert_eq!(result, expected)
     }
 
@@ -334,7 +463,7 @@ mod tests {
     fn lex_mult() {
         let mut expected = Vec::new();
         expected.push(OPE(MULTIPLICATION));
-        let result = lex("*".to_string());
+        let result = lex("*".to_string()).unwrap();
         assert_eq!(result, expected)
     }
 
@@ -342,7 +471,7 @@ mod tests {
     fn lex_divide() {
         let mut expected = Vec::new();
         expected.push(OPE(DIVIDE));
-        let result = lex("/".to_string());
+        let result = lex("/".to_string()).unwrap();
         assert_eq!(result, expected)
     }
 
@@ -353,7 +482,7 @@ mod tests {
         expected.push(OPE(MULTIPLICATION));
         expected.push(OPE(MINUS));
         expected.push(OPE(DIVIDE));
-        let result = lex("+*-/".to_string());
+        let result = lex("+*-/".to_string()).unwrap();
         assert_eq!(result, expected)
     }
 
@@ -361,7 +490,7 @@ mod tests {
     fn lex_lpar() {
         let mut expected = Vec::new();
         expected.push(LPAR);
-        let result = lex("(".to_string());
+        let result = lex("(".to_string()).unwrap();
         assert_eq!(result, expected)
     }
 
@@ -369,7 +498,7 @@ mod tests {
     fn lex_rpar() {
         let mut expected = Vec::new();
         expected.push(RPAR);
-        let result = lex(")".to_string());
+        let result = lex(")".to_string()).unwrap();
         assert_eq!(result, expected)
     }
 
@@ -377,7 +506,7 @@ mod tests {
     fn lex_equal() {
         let mut expected = Vec::new();
         expected.push(EQUAL);
-        let result = lex("=".to_string());
+        let result = lex("=".to_string()).unwrap();
         assert_eq!(result, expected);
     }
 
@@ -387,7 +516,7 @@ mod tests {
         expected.push(LPAR);
         expected.push(RPAR);
         expected.push(EQUAL);
-        let result = lex("()=".to_string());
+        let result = lex("()=".to_string()).unwrap();
         assert_eq!(result, expected)
     }
 
@@ -395,7 +524,7 @@ mod tests {
     fn lex_simple_int() {
         let mut expected = Vec::new();
         expected.push(INT(1));
-        let result = lex("1".to_string());
+        let result = lex("1".to_string()).unwrap();
         assert_eq!(result, expected);
     }
 
@@ -403,7 +532,7 @@ mod tests {
     fn lex_complex_int() {
         let mut expected = Vec::new();
         expected.push(INT(100));
-        let result = lex("100".to_string());
+        let result = lex("100".to_string()).unwrap();
         assert_eq!(result, expected);
     }
 
@@ -411,7 +540,7 @@ mod tests {
     fn lex_simple_string() {
         let mut expected = Vec::new();
         expected.push(IDENTIFIER("test".to_string()));
-        let result = lex("test".to_string());
+        let result = lex("test".to_string()).unwrap();
         assert_eq!(result, expected);
     }
 
@@ -421,7 +550,7 @@ mod tests {
         expected.push(INT(1));
         expected.push(OPE(PLUS));
         expected.push(INT(1));
-        let result = lex("1 + 1".to_string());
+        let result = lex("1 + 1".to_string()).unwrap();
         assert_eq!(result, expected);
     }
 
@@ -431,7 +560,7 @@ mod tests {
         expected.push(IDENTIFIER("var1".to_string()));
         expected.push(EQUAL);
         expected.push(INT(100));
-        let result = lex("var1 = 100".to_string());
+        let result = lex("var1 = 100".to_string()).unwrap();
         assert_eq!(result, expected)
     }
 
@@ -439,7 +568,7 @@ mod tests {
     fn test_simple_float() {
         let mut expected = Vec::new();
         expected.push(FLOAT(0.14));
-        let result = lex(".14".to_string());
+        let result = lex(".14".to_string()).unwrap();
         assert_eq!(result, expected);
     }
 
@@ -447,7 +576,236 @@ mod tests {
     fn test_complex_float() {
         let mut expected = Vec::new();
         expected.push(FLOAT(314.05));
-        let result = lex("314.05".to_string());
+        let result = lex("314.05".to_string()).unwrap();
         assert_eq!(result, expected)
     }
+
+    #[test]
+    fn test_spans() {
+        let result = lex("x <= 10".to_string()).unwrap();
+        let spans: Vec<Span> = result.iter().map(|t| t.span).collect();
+        assert_eq!(
+            spans,
+            vec![Span::new(0, 1), Span::new(2, 4), Span::new(5, 7)]
+        );
+    }
+
+    #[test]
+    fn test_unknown_character() {
+        let expected = ParseError::new(ParseErrorKind::UnknownCharacter('$'), Span::new(4, 5));
+        let result = lex("1 + $".to_string());
+        assert_eq!(result, Err(expected));
+    }
+
+    #[test]
+    fn test_unclosed_quote() {
+        let expected = ParseError::new(ParseErrorKind::Unclosed('"'), Span::new(5, 6));
+        let result = lex("plot(\"title)".to_string());
+        assert_eq!(result, Err(expected));
+    }
+
+    #[test]
+    fn test_big_int() {
+        let expected = vec![BIGINT("123456789012345678901234567890".parse().unwrap())];
+        let result = lex("123456789012345678901234567890".to_string()).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_scientific() {
+        let expected = vec![
+            FLOAT(1e-9),
+            OPE(PLUS),
+            FLOAT(6.022e23),
+            OPE(PLUS),
+            FLOAT(1500.0),
+        ];
+        let result = lex("1e-9 + 6.022E23 + 1.5e+3".to_string()).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_malformed_number() {
+        let expected = ParseError::new(ParseErrorKind::MalformedNumber, Span::new(0, 3));
+        let result = lex("1e+".to_string());
+        assert_eq!(result, Err(expected));
+    }
+
+    #[test]
+    fn test_number_out_of_range() {
+        let expected = ParseError::new(ParseErrorKind::NumberOutOfRange, Span::new(4, 9));
+        let result = lex("2 + 1e400 * 3".to_string());
+        assert_eq!(result, Err(expected));
+        let expected = ParseError::new(ParseErrorKind::NumberOutOfRange, Span::new(0, 9));
+        assert_eq!(lex("1.5e+9999".to_string()), Err(expected));
+        assert_eq!(lex("1e-400".to_string()).unwrap(), vec![FLOAT(0.0)]);
+    }
+
+    #[test]
+    fn test_radix_literals() {
+        let expected = vec![INT(31), OPE(PLUS), INT(11), OPE(PLUS), INT(15)];
+        let result = lex("0x1F + 0b1011 + 0o17".to_string()).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_malformed_radix_literal() {
+        let expected = ParseError::new(ParseErrorKind::MalformedNumber, Span::new(0, 5));
+        let result = lex("0b102".to_string());
+        assert_eq!(result, Err(expected));
+    }
+
+    #[test]
+    fn test_bitwise_operators() {
+        let expected = vec![
+            INT(1),
+            OPE(BitAnd),
+            INT(2),
+            OPE(BitOr),
+            OPE(BitNot),
+            INT(3),
+            OPE(Xor),
+            INT(4),
+            OPE(ShiftLeft),
+            INT(5),
+            OPE(ShiftRight),
+            INT(6),
+            OPE(And),
+            OPE(Or),
+        ];
+        let result = lex("1 & 2 | ~3 xor 4 << 5 >> 6 && ||".to_string()).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_postfix_tokens() {
+        let expected = vec![
+            INT(5),
+            OPE(NOT),
+            PERCENT,
+            IDENTIFIER("f".to_string()),
+            PRIME,
+            OPE(NotEqual),
+        ];
+        let result = lex("5!% f' !=".to_string()).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_factorial_before_equal() {
+        let expected = vec![INT(5), OPE(NOT), OPE(EQUALITY), INT(120)];
+        assert_eq!(lex("5! == 120".to_string()).unwrap(), expected);
+        let expected = vec![INT(5), OPE(NOT), EQUAL, INT(3)];
+        assert_eq!(lex("5! =3".to_string()).unwrap(), expected);
+        let expected = vec![IDENTIFIER("x".to_string()), OPE(NotEqual), INT(3)];
+        assert_eq!(lex("x != 3".to_string()).unwrap(), expected);
+        assert_eq!(lex("x!=3".to_string()).unwrap(), expected);
+        let expected = vec![RPAR, OPE(NotEqual), INT(3)];
+        assert_eq!(lex(")!=3".to_string()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_imaginary_literals() {
+        let expected = vec![
+            IMAGINARY(3.0),
+            OPE(PLUS),
+            IMAGINARY(0.5),
+            OPE(PLUS),
+            IMAGINARY(2e3),
+        ];
+        assert_eq!(lex("3i + 0.5i + 2e3i".to_string()).unwrap(), expected);
+        let expected = vec![INT(3), IDENTIFIER("i".to_string())];
+        assert_eq!(lex("3 i".to_string()).unwrap(), expected);
+        let expected = vec![INT(3), IDENTIFIER("is".to_string())];
+        assert_eq!(lex("3is".to_string()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_modulo_tokens() {
+        let expected = vec![INT(7), OPE(Mod), INT(3), OPE(FloorDivide), INT(2)];
+        let result = lex("7 mod 3 // 2".to_string()).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_statements_and_comments() {
+        let expected = vec![INT(1), SEMICOLON, INT(2), SEMICOLON, INT(3)];
+        let result = lex("1; 2 # two; 2\n3".to_string()).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_arrow() {
+        let expected = vec![
+            IDENTIFIER("x".to_string()),
+            ARROW,
+            IDENTIFIER("x".to_string()),
+            OPE(MINUS),
+            INT(1),
+        ];
+        let result = lex("x -> x-1".to_string()).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_range_and_slice_tokens() {
+        let expected = vec![INT(1), RANGE, INT(3)];
+        assert_eq!(lex("1..3".to_string()).unwrap(), expected);
+        let expected = vec![ELLIPSIS, IDENTIFIER("args".to_string())];
+        assert_eq!(lex("...args".to_string()).unwrap(), expected);
+        let expected = vec![
+            IDENTIFIER("v".to_string()),
+            LBRACKET,
+            FLOAT(1.5),
+            COLON,
+            RBRACKET,
+        ];
+        assert_eq!(lex("v[1.5:]".to_string()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_loop_tokens() {
+        let expected = vec![
+            FOR,
+            IDENTIFIER("i".to_string()),
+            IN,
+            IDENTIFIER("v".to_string()),
+            LBRACE,
+            IDENTIFIER("i".to_string()),
+            SEMICOLON,
+            RBRACE,
+            SEMICOLON,
+            WHILE,
+            BOOL(false),
+            LBRACE,
+            RBRACE,
+        ];
+        let result = lex("for i in v { i; }\nwhile false {}".to_string()).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_compound_assignment() {
+        let expected = vec![
+            IDENTIFIER("x".to_string()),
+            COMPOUND(MULTIPLICATION),
+            INT(2),
+            SEMICOLON,
+            IDENTIFIER("x".to_string()),
+            EQUAL,
+            OPE(MINUS),
+            INT(1),
+        ];
+        let result = lex("x *= 2; x =-1".to_string()).unwrap();
+        assert_eq!(result, expected);
+        let expected = vec![
+            COMPOUND(Mod),
+            COMPOUND(FloorDivide),
+            COMPOUND(EXPO),
+            PERCENT,
+            OPE(EQUALITY),
+        ];
+        let result = lex("%= //= ^= %==".to_string()).unwrap();
+        assert_eq!(result, expected);
+    }
 }
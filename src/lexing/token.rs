@@ -1,5 +1,7 @@
 use std::fmt::{Display, Formatter};
 
+use num_bigint::BigInt;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Operator {
     PLUS,
@@ -8,6 +10,7 @@ pub enum Operator {
     DIVIDE,
     EXPO,
     EQUALITY,
+    NotEqual,
     GreaterThan,
     LesserThan,
     GreaterOrEqual,
@@ -15,6 +18,14 @@ pub enum Operator {
     And,
     Or,
     NOT,
+    BitAnd,
+    BitOr,
+    Xor,
+    BitNot,
+    ShiftLeft,
+    ShiftRight,
+    Mod,
+    FloorDivide,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -22,7 +33,10 @@ pub enum Token {
     OPE(Operator),
     IDENTIFIER(String),
     INT(i64),
+    BIGINT(BigInt),
     FLOAT(f64),
+    /// A number directly followed by `i`, `3i` or `0.5i`.
+    IMAGINARY(f64),
     BOOL(bool),
     EQUAL,
     RPAR,
@@ -33,8 +47,39 @@ pub enum Token {
     Null,
     QUOTE,
     WHITESPACE,
-    PreAnd,
-    PreOr,
+    PERCENT,
+    PRIME,
+    SEMICOLON,
+    IF,
+    THEN,
+    ELSE,
+    DEL,
+    MEMO,
+    ARROW,
+    COLON,
+    RANGE,
+    RBRACE,
+    LBRACE,
+    FOR,
+    IN,
+    WHILE,
+    WHEN,
+    ELLIPSIS,
+    /// An operator followed by `=`, `x += 1` is `x = x + 1`.
+    COMPOUND(Operator),
+}
+
+/// Byte range of a token in the lexed input, `end` being exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
@@ -48,6 +93,7 @@ pub enum TokenType {
     FLOAT,
     EQUAL,
     EQUALITY,
+    NOTEQUAL,
     GREATER,
     LESSER,
     GREATEREQ,
@@ -65,19 +111,49 @@ pub enum TokenType {
     WHITESPACE,
     EXPO,
     QUOTE,
+    BITAND,
+    BITOR,
+    XOR,
+    BITNOT,
+    SHIFTLEFT,
+    SHIFTRIGHT,
+    PERCENT,
+    PRIME,
+    MOD,
+    FLOORDIVIDE,
+    SEMICOLON,
+    IF,
+    THEN,
+    ELSE,
+    DEL,
+    MEMO,
+    ARROW,
+    COLON,
+    RANGE,
+    RBRACE,
+    LBRACE,
+    FOR,
+    IN,
+    WHILE,
+    WHEN,
+    ELLIPSIS,
+    COMPOUND,
 }
 
 pub enum Precedence {
     ASSIGNMENT = 1,
     CONDITIONAL = 2,
-    SUM = 4,
-    MINUS = 3,
-    PRODUCT = 6,
-    DIVIDE = 5,
-    EXPONENT = 7,
-    //PREFIX = 8,
-    //POSTFIX = 9,
-    CALL = 10,
+    RANGE = 3,
+    BITOR = 4,
+    XOR = 5,
+    BITAND = 6,
+    SHIFT = 7,
+    SUM = 8,
+    PRODUCT = 9,
+    PREFIX = 10,
+    EXPONENT = 11,
+    POSTFIX = 12,
+    CALL = 13,
 }
 
 impl Display for Operator {
@@ -89,6 +165,7 @@ impl Display for Operator {
             Operator::MULTIPLICATION => write!(f, "*"),
             Operator::EXPO => write!(f, "^"),
             Operator::EQUALITY => write!(f, "=="),
+            Operator::NotEqual => write!(f, "!="),
             Operator::GreaterOrEqual => write!(f, ">="),
             Operator::GreaterThan => write!(f, ">"),
             Operator::LesserOrEqual => write!(f, "<="),
@@ -96,6 +173,14 @@ impl Display for Operator {
             Operator::NOT => write!(f, "!"),
             Operator::Or => write!(f, "||"),
             Operator::And => write!(f, "&&"),
+            Operator::BitAnd => write!(f, "&"),
+            Operator::BitOr => write!(f, "|"),
+            Operator::Xor => write!(f, "xor"),
+            Operator::BitNot => write!(f, "~"),
+            Operator::ShiftLeft => write!(f, "<<"),
+            Operator::ShiftRight => write!(f, ">>"),
+            Operator::Mod => write!(f, "mod"),
+            Operator::FloorDivide => write!(f, "//"),
         }
     }
 }
@@ -107,22 +192,59 @@ impl Display for Token {
             Token::RPAR => write!(f, ")"),
             Token::EQUAL => write!(f, "="),
             Token::FLOAT(i) => write!(f, "{}", i),
+            Token::IMAGINARY(i) => write!(f, "{}i", i),
             Token::INT(i) => write!(f, "{}", i),
+            Token::BIGINT(i) => write!(f, "{}", i),
             Token::IDENTIFIER(s) => write!(f, "{}", s),
             Token::OPE(s) => write!(f, "{}", s),
             Token::COMMA => write!(f, ","),
             Token::Null => write!(f, "Null"),
             Token::BOOL(b) => write!(f, "{b}"),
-            Token::PreAnd => write!(f, ""),
-            Token::PreOr => write!(f, ""),
             Token::RBRACKET => write!(f, "]"),
             Token::LBRACKET => write!(f, "["),
             Token::QUOTE => write!(f, "\""),
+            Token::PERCENT => write!(f, "%"),
+            Token::PRIME => write!(f, "'"),
+            Token::SEMICOLON => write!(f, ";"),
+            Token::IF => write!(f, "if"),
+            Token::THEN => write!(f, "then"),
+            Token::ELSE => write!(f, "else"),
+            Token::DEL => write!(f, "del"),
+            Token::MEMO => write!(f, "memo"),
+            Token::ARROW => write!(f, "->"),
+            Token::COLON => write!(f, ":"),
+            Token::RANGE => write!(f, ".."),
+            Token::RBRACE => write!(f, "}}"),
+            Token::LBRACE => write!(f, "{{"),
+            Token::FOR => write!(f, "for"),
+            Token::IN => write!(f, "in"),
+            Token::WHILE => write!(f, "while"),
+            Token::WHEN => write!(f, "when"),
+            Token::ELLIPSIS => write!(f, "..."),
+            Token::COMPOUND(op) => write!(f, "{op}="),
             Token::WHITESPACE => write!(f, " "),
         }
     }
 }
 
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+}
+
+impl SpannedToken {
+    pub fn new(token: Token, span: Span) -> Self {
+        SpannedToken { token, span }
+    }
+}
+
+impl PartialEq<Token> for SpannedToken {
+    fn eq(&self, other: &Token) -> bool {
+        self.token == *other
+    }
+}
+
 impl Token {
     pub fn to_token_type(&self) -> TokenType {
         match &self {
@@ -133,6 +255,7 @@ impl Token {
                 Operator::DIVIDE => TokenType::DIVIDE,
                 Operator::EXPO => TokenType::EXPO,
                 Operator::EQUALITY => TokenType::EQUALITY,
+                Operator::NotEqual => TokenType::NOTEQUAL,
                 Operator::GreaterThan => TokenType::GREATER,
                 Operator::GreaterOrEqual => TokenType::GREATEREQ,
                 Operator::LesserThan => TokenType::LESSER,
@@ -140,10 +263,18 @@ impl Token {
                 Operator::NOT => TokenType::NOT,
                 Operator::And => TokenType::AND,
                 Operator::Or => TokenType::OR,
+                Operator::BitAnd => TokenType::BITAND,
+                Operator::BitOr => TokenType::BITOR,
+                Operator::Xor => TokenType::XOR,
+                Operator::BitNot => TokenType::BITNOT,
+                Operator::ShiftLeft => TokenType::SHIFTLEFT,
+                Operator::ShiftRight => TokenType::SHIFTRIGHT,
+                Operator::Mod => TokenType::MOD,
+                Operator::FloorDivide => TokenType::FLOORDIVIDE,
             },
             Token::IDENTIFIER(_) => TokenType::IDENTIFIER,
-            Token::INT(_) => TokenType::INT,
-            Token::FLOAT(_) => TokenType::FLOAT,
+            Token::INT(_) | Token::BIGINT(_) => TokenType::INT,
+            Token::FLOAT(_) | Token::IMAGINARY(_) => TokenType::FLOAT,
             Token::EQUAL => TokenType::EQUAL,
             Token::RPAR => TokenType::RPAR,
             Token::LPAR => TokenType::LPAR,
@@ -153,8 +284,26 @@ impl Token {
             Token::LBRACKET => TokenType::LBRACKET,
             Token::RBRACKET => TokenType::RBRACKET,
             Token::QUOTE => TokenType::QUOTE,
+            Token::PERCENT => TokenType::PERCENT,
+            Token::PRIME => TokenType::PRIME,
+            Token::SEMICOLON => TokenType::SEMICOLON,
+            Token::IF => TokenType::IF,
+            Token::THEN => TokenType::THEN,
+            Token::ELSE => TokenType::ELSE,
+            Token::DEL => TokenType::DEL,
+            Token::MEMO => TokenType::MEMO,
+            Token::ARROW => TokenType::ARROW,
+            Token::COLON => TokenType::COLON,
+            Token::RANGE => TokenType::RANGE,
+            Token::RBRACE => TokenType::RBRACE,
+            Token::LBRACE => TokenType::LBRACE,
+            Token::FOR => TokenType::FOR,
+            Token::IN => TokenType::IN,
+            Token::WHILE => TokenType::WHILE,
+            Token::WHEN => TokenType::WHEN,
+            Token::ELLIPSIS => TokenType::ELLIPSIS,
+            Token::COMPOUND(_) => TokenType::COMPOUND,
             Token::WHITESPACE => TokenType::WHITESPACE,
-            _ => TokenType::Null,
         }
     }
 }
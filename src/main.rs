@@ -5,19 +5,24 @@ use std::f64::consts::{E, PI};
 use std::process::{exit, Command};
 use std::str::SplitWhitespace;
 use std::sync::Arc;
+use std::thread;
 
 use ansi_term::Color;
 use configuration::loader::Config;
+use exact_math::complex_mode::ComplexMode;
 use exact_math::float_mode::FloatMode;
+use exact_math::programmer_mode::{IntType, Overflow, ProgrammerMode};
+use exact_math::radix_mode::RadixMode;
 use linefeed::{Completer, Completion, Interface, ReadResult, Terminal};
 
 use crate::configuration::loader::{
     load, load_config, write_config, write_default_config, Greeting, Loaded, Prompt,
 };
-use crate::interpreting::interpreter::interpret;
+use crate::interpreting::interpreter::interpret_statements;
+use crate::interpreting::memo;
 use crate::lexing::lexer::lex;
-use crate::parsing::ast::{Ast, Parameters};
-use crate::parsing::parser::{init_calc_parser, CalcParser};
+use crate::parsing::ast::{Functions, Parameters, Ram};
+use crate::parsing::parser::init_calc_parser;
 use atty::Stream;
 use std::io;
 use std::io::BufRead;
@@ -31,6 +36,20 @@ mod parsing;
 mod utils;
 
 thread_local! {static FLOAT_MODE: RefCell<FloatMode> = const {RefCell::new(FloatMode::Exact)}}
+thread_local! {static COMPLEX_MODE: RefCell<ComplexMode> = const {RefCell::new(ComplexMode::Rectangular)}}
+thread_local! {static RADIX_MODE: RefCell<RadixMode> = const {RefCell::new(RadixMode::Decimal)}}
+thread_local! {static PROGRAMMER_MODE: RefCell<ProgrammerMode> = const {RefCell::new(ProgrammerMode::Off)}}
+thread_local! {static RECURSION_LIMIT: RefCell<usize> = const {RefCell::new(DEFAULT_RECURSION_LIMIT)}}
+static DEFAULT_RECURSION_LIMIT: usize = 1000;
+/// Stack of the thread running the calculator, the default one of the main
+/// thread overflows well before the recursion limit of user functions.
+static STACK_SIZE: usize = 256 * 1024 * 1024;
+/// Stack taken by a call to a user function, with some nesting in its body,
+/// in a debug build.
+static CALL_STACK_SIZE: usize = 128 * 1024;
+/// Deeper recursions would overflow `STACK_SIZE`, a larger limit in the config
+/// is lowered to it.
+static MAX_RECURSION_LIMIT: usize = STACK_SIZE / CALL_STACK_SIZE;
 static VERSION: &str = "v3.4.2";
 
 fn show_config(config: Config) -> (String, Option<Config>) {
@@ -46,7 +65,10 @@ fn show_config(config: Config) -> (String, Option<Config>) {
     let general_message_color = loaded.general_color.paint(config.general_color);
     let general_message = loaded.general_color.paint("This is the general colour");
     let float_mode = loaded.general_color.paint(config.default_float_mode);
-    println!("The greeting colour is set to {} which prints \n {} \nThe prompt is \"{}\" in {} \nDefault Float Mode is currently {} \nMain colour is {} which looks like \n {} \nIf you've modified your config and it doesn't look good, the author (Charlotte Thomas) declines any responsabilities.\n", color_message, show_message,prompt,prompt_color_message,float_mode,general_message_color,general_message);
+    let recursion_limit = loaded
+        .general_color
+        .paint(config.recursion_limit.to_string());
+    println!("The greeting colour is set to {} which prints \n {} \nThe prompt is \"{}\" in {} \nDefault Float Mode is currently {} \nRecursion limit is currently {} \nMain colour is {} which looks like \n {} \nIf you've modified your config and it doesn't look good, the author (Charlotte Thomas) declines any responsabilities.\n", color_message, show_message,prompt,prompt_color_message,float_mode,recursion_limit,general_message_color,general_message);
     ("".to_string(), None)
 }
 
@@ -90,6 +112,7 @@ fn set_config(config: Config, args: &mut SplitWhitespace) -> (String, Option<Con
                             default_float_mode: (config.default_float_mode),
                             greeting: (config.greeting),
                             prompt: (config.prompt),
+                            recursion_limit: config.recursion_limit,
                         };
                         match write_config(&cfg) {
                             Ok(_) => (format!("Greeting color has been set to {}, reload for this to take effect\n",&s).to_string(),None),
@@ -115,6 +138,7 @@ fn set_config(config: Config, args: &mut SplitWhitespace) -> (String, Option<Con
                                 prompt: s.to_string(),
                                 prompt_color: config.prompt.prompt_color,
                             },
+                            recursion_limit: config.recursion_limit,
                         };
 
                         match write_config(&cfg) {
@@ -152,6 +176,7 @@ fn set_config(config: Config, args: &mut SplitWhitespace) -> (String, Option<Con
                                 prompt: config.prompt.prompt,
                                 prompt_color: s.to_string(),
                             },
+                            recursion_limit: config.recursion_limit,
                         };
 
                         match write_config(&cfg) {
@@ -180,6 +205,7 @@ fn set_config(config: Config, args: &mut SplitWhitespace) -> (String, Option<Con
                                 greeting_message: config.greeting.greeting_message,
                             },
                             prompt: config.prompt,
+                            recursion_limit: config.recursion_limit,
                         };
 
                         match write_config(&cfg) {
@@ -203,6 +229,30 @@ fn set_config(config: Config, args: &mut SplitWhitespace) -> (String, Option<Con
                     _ => ("An error occured while updating config\n".to_string(),None)
                 }
             }
+            Some("recursion_limit") => {
+                let limit = match args.next().map(|x| x.parse::<usize>()) {
+                    None => return ("Not enough parameters".to_string(), None),
+                    Some(Ok(limit)) if limit > MAX_RECURSION_LIMIT => {
+                        return (
+                            format!("The recursion limit cannot exceed {MAX_RECURSION_LIMIT}\n"),
+                            None,
+                        )
+                    }
+                    Some(Ok(limit)) if limit > 0 => limit,
+                    Some(_) => {
+                        return (
+                            "The recursion limit must be a positive integer\n".to_string(),
+                            None,
+                        )
+                    }
+                };
+                let mut cfg: Config = config.clone();
+                cfg.recursion_limit = limit;
+                match write_config(&cfg) {
+                    Ok(_) => (format!("You updated the recursion limit to {}, reload for this to take effect\n",limit),None),
+                    _ => ("An error occured while updating config\n".to_string(),None)
+                }
+            }
             Some("greeting_message") => {
                 let mut st = "".to_string();
                 args.into_iter().for_each(|x| st = st.clone() + x + " ");
@@ -221,6 +271,7 @@ fn set_config(config: Config, args: &mut SplitWhitespace) -> (String, Option<Con
                             },
                             default_float_mode: config.default_float_mode,
                             prompt: config.prompt,
+                            recursion_limit: config.recursion_limit,
                         };
 
                         match write_config(&cfg) {
@@ -283,7 +334,62 @@ fn handle_config(line: &str, config: Config) -> (String, Option<Config>) {
     }
 }
 
+/// Variables defined at startup, and again after `clear`.
+fn init_ram() -> Ram {
+    let mut ram: Ram = HashMap::new();
+    ram.insert("pi".to_string(), Parameters::Float(PI));
+    ram.insert("e".to_string(), Parameters::Float(E));
+    ram
+}
+
+fn show_variables(ram: &mut Ram, functions: &mut Functions) {
+    let mut names: Vec<String> = ram.keys().cloned().collect();
+    names.sort();
+    for name in names {
+        let value = ram[&name].clone();
+        println!(
+            "{} = {}",
+            Color::Yellow.paint(&name),
+            Color::Yellow.paint(value.pretty_print(Some(ram), Some(functions)))
+        );
+    }
+}
+
+fn show_functions(functions: &Functions) {
+    let mut names: Vec<&String> = functions.keys().collect();
+    names.sort();
+    for name in names {
+        for equation in &functions[name] {
+            println!(
+                "{} = {}",
+                Color::RGB(255, 215, 0).paint(equation.head(name)),
+                Color::RGB(255, 215, 0).paint(equation.body.to_string())
+            );
+        }
+    }
+}
+
+fn show_cache() {
+    for (name, size) in memo::sizes() {
+        let results = if size == 1 { "result" } else { "results" };
+        println!(
+            "{}: {size} cached {results}",
+            Color::RGB(255, 215, 0).paint(name)
+        );
+    }
+}
+
 fn main() {
+    let calc = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .expect("could not start the calculator thread");
+    if calc.join().is_err() {
+        exit(1);
+    }
+}
+
+fn run() {
     let mut args: Args = env::args();
 
     if args.len() > 1 || !atty::is(Stream::Stdin) {
@@ -299,7 +405,7 @@ fn main() {
             args.for_each(|f| a.push(f));
         }
 
-        let arg_final = a.join("");
+        let arg_final = a.join("\n");
         if arg_final == "-h" || arg_final == "--help" {
             println!("-----Help Calc-----");
             println!("");
@@ -334,14 +440,18 @@ fn main() {
             exit(0);
         }
 
-        let lexed = lex(arg_final);
-        let mut parser = init_calc_parser(&lexed);
-        let parsed = parser.parse();
-        let mut ram: HashMap<String, Parameters> = HashMap::new();
-        let mut functions: HashMap<String, (Vec<Ast>, Ast)> = HashMap::new();
-        ram.insert("pi".to_string(), Parameters::Float(PI));
-        ram.insert("e".to_string(), Parameters::Float(E));
-        let result = interpret(&parsed, &mut ram, &mut functions);
+        let parsed = match lex(arg_final.clone())
+            .and_then(|lexed| init_calc_parser(&lexed).parse_statements())
+        {
+            Ok(parsed) => parsed,
+            Err(error) => {
+                println!("{}", error.render(&arg_final));
+                exit(1);
+            }
+        };
+        let mut ram: Ram = init_ram();
+        let mut functions: Functions = HashMap::new();
+        let result = interpret_statements(&parsed, &mut ram, &mut functions);
         if result != Parameters::Null {
             println!(
                 "{}",
@@ -370,6 +480,7 @@ fn main() {
     FLOAT_MODE.with(|fm| {
         *fm.borrow_mut() = loaded.clone().float_mode;
     });
+    RECURSION_LIMIT.with(|rl| *rl.borrow_mut() = loaded.recursion_limit);
 
     let message = &loaded.greeting_message;
     println!("{}", message.to_string());
@@ -388,10 +499,8 @@ fn main() {
         ))
         .unwrap();
 
-    let mut ram: HashMap<String, Parameters> = HashMap::new();
-    let mut functions: HashMap<String, (Vec<Ast>, Ast)> = HashMap::new();
-    ram.insert("pi".to_string(), Parameters::Float(PI));
-    ram.insert("e".to_string(), Parameters::Float(E));
+    let mut ram: Ram = init_ram();
+    let mut functions: Functions = HashMap::new();
     while let ReadResult::Input(line) = interface.read_line().unwrap() {
         match line.as_str().trim() {
             "info" => {
@@ -401,10 +510,33 @@ fn main() {
             "exit" => break,
             "help" => {
                 let message = loaded.general_color.paint(format!(
-                    " Calc {VERSION} Help \n > info : show infos \n > exit : exit the program \n > help : print this help \n > verbose : toggle the verbose \n > version : prints the version \n > config : root of the config \n toggle_float <exact|science|normal> : toggle the float mode"
+                    " Calc {VERSION} Help \n > info : show infos \n > exit : exit the program \n > help : print this help \n > verbose : toggle the verbose \n > version : prints the version \n > config : root of the config \n toggle_float <exact|science|normal> : toggle the float mode \n toggle_complex <rectangular|polar> : toggle the complex mode \n toggle_radix <decimal|hex|binary|octal> : toggle the integer display base \n toggle_programmer <off|u8|i16|...|u128> [wrapping|saturating|checked] : toggle the fixed-width integers \n > vars : list the variables \n > funcs : list the functions \n > clear : remove every variable and function \n del <name> : remove a variable or a function \n memo <name> : cache the results of a pure function \n > cache [clear] : list or empty the caches of the memoized functions"
                 ));
                 println!("{}", message)
             }
+            "vars" => show_variables(&mut ram, &mut functions),
+            "funcs" => show_functions(&functions),
+            "cache" => show_cache(),
+            "cache clear" => {
+                memo::clear();
+                println!(
+                    "{}",
+                    loaded
+                        .general_color
+                        .paint("The caches of the memoized functions have been cleared")
+                );
+            }
+            "clear" => {
+                ram = init_ram();
+                functions.clear();
+                memo::reset();
+                println!(
+                    "{}",
+                    loaded
+                        .general_color
+                        .paint("All variables and functions have been cleared")
+                );
+            }
             "VERSION" => {
                 let message = loaded.general_color.paint(format!(" Calc {VERSION}\n"));
                 println!("{}", message)
@@ -416,6 +548,7 @@ fn main() {
                 println!("{}{}", message, message2)
             }
             str if str.starts_with("toggle_float") => {
+                memo::clear();
                 let p = str.replace("toggle_float ", "");
                 match p.as_str().trim() {
                     "exact" | "rational" => FLOAT_MODE.with(|fm| {
@@ -447,6 +580,65 @@ fn main() {
                     }),
                 }
             }
+            str if str.starts_with("toggle_complex") => {
+                let p = str.replace("toggle_complex", "");
+                let mode = match p.as_str().trim() {
+                    "polar" => ComplexMode::Polar,
+                    _ => ComplexMode::Rectangular,
+                };
+                let message = loaded
+                    .general_color
+                    .paint("You toggled the complex mode to :");
+                let message2 = Color::Red.paint(format!("{mode} mode."));
+                let message3 = loaded.general_color.paint(match mode {
+                    ComplexMode::Polar => "Example: 1+i=1.4142135624∠0.7853981634",
+                    ComplexMode::Rectangular => "Example: 1+i=1+i",
+                });
+                COMPLEX_MODE.with(|cm| *cm.borrow_mut() = mode);
+                memo::clear();
+                println!("{} {}\n{}", message, message2, message3);
+            }
+            str if str.starts_with("toggle_radix") => {
+                let p = str.replace("toggle_radix", "");
+                let (mode, example) = match p.as_str().trim() {
+                    "hex" | "hexadecimal" => (RadixMode::Hexadecimal, "Example: 31=0x1f"),
+                    "bin" | "binary" => (RadixMode::Binary, "Example: 31=0b11111"),
+                    "oct" | "octal" => (RadixMode::Octal, "Example: 31=0o37"),
+                    _ => (RadixMode::Decimal, "Example: 0x1f=31"),
+                };
+                let message = loaded
+                    .general_color
+                    .paint("You toggled the radix mode to :");
+                let message2 = Color::Red.paint(format!("{mode} mode."));
+                let message3 = loaded.general_color.paint(example);
+                RADIX_MODE.with(|rm| *rm.borrow_mut() = mode);
+                println!("{} {}\n{}", message, message2, message3);
+            }
+            str if str.starts_with("toggle_programmer") => {
+                let p = str.replace("toggle_programmer", "");
+                let mut words = p.split_whitespace();
+                let int_type = words.next().and_then(IntType::parse);
+                let overflow = match words.next() {
+                    Some("saturating") => Overflow::Saturating,
+                    Some("checked") => Overflow::Checked,
+                    _ => Overflow::Wrapping,
+                };
+                let mode = match int_type {
+                    Some(t) => ProgrammerMode::Fixed(t, overflow),
+                    None => ProgrammerMode::Off,
+                };
+                let message = loaded
+                    .general_color
+                    .paint("You toggled the programmer mode to :");
+                let message2 = Color::Red.paint(format!("{mode} mode."));
+                let message3 = loaded.general_color.paint(match mode {
+                    ProgrammerMode::Off => "Example: 255+1=256".to_string(),
+                    ProgrammerMode::Fixed(t, o) => format!("Example in {mode}: {}", t.example(o)),
+                });
+                PROGRAMMER_MODE.with(|pm| *pm.borrow_mut() = mode);
+                memo::clear();
+                println!("{} {}\n{}", message, message2, message3);
+            }
             str => {
                 if str.starts_with("config") {
                     let (s, q) = handle_config(&line, config.clone());
@@ -457,6 +649,8 @@ fn main() {
                             FLOAT_MODE.with(|fm| {
                                 *fm.borrow_mut() = loaded.float_mode;
                             });
+                            memo::clear();
+                            RECURSION_LIMIT.with(|rl| *rl.borrow_mut() = loaded.recursion_limit);
                             text = &loaded.prompt;
                             interface
                                 .set_prompt(&format!(
@@ -474,18 +668,27 @@ fn main() {
                         }
                     }
                 } else {
-                    let a = lex(str.to_string());
-                    let parser: &mut CalcParser = &mut parsing::parser::init_calc_parser(&a);
-                    let p = parser.parse();
+                    let lexed = lex(str.to_string());
                     if verbose {
                         println!("Lexing of line: {str}");
-                        println!("{:?}", &a);
-                        println!("Parsing of line: {str}");
-                        println!("{:#?}", p);
-                        println!()
+                        println!("{:?}", &lexed);
                     }
+                    let parsed = lexed.and_then(|a| init_calc_parser(&a).parse_statements());
+                    let result = match parsed {
+                        Ok(p) => {
+                            if verbose {
+                                println!("Parsing of line: {str}");
+                                println!("{:#?}", p);
+                                println!()
+                            }
 
-                    let result = interpret(&p, &mut ram, &mut functions);
+                            interpret_statements(&p, &mut ram, &mut functions)
+                        }
+                        Err(error) => {
+                            println!("{}", error.render(str));
+                            Parameters::Null
+                        }
+                    };
 
                     if verbose {
                         println!("{:#?}", &result);
@@ -516,9 +719,22 @@ static CMD: &[&str] = &[
     "help",
     "info",
     "toggle_float",
+    "toggle_complex",
+    "toggle_radix",
+    "toggle_programmer",
+    "vars",
+    "funcs",
+    "clear",
+    "cache",
 ];
 static CONFIG_CMD: &[&str] = &["reload", "reset", "set", "show"];
 static TOGGLE_FLOAT_CMD: &[&str] = &["normal", "science", "scientific", "exact", "rational"];
+static TOGGLE_COMPLEX_CMD: &[&str] = &["rectangular", "polar"];
+static TOGGLE_RADIX_CMD: &[&str] = &["decimal", "hex", "binary", "octal"];
+static TOGGLE_PROGRAMMER_CMD: &[&str] = &[
+    "off", "u8", "i8", "u16", "i16", "u32", "i32", "u64", "i64", "u128", "i128",
+];
+static OVERFLOW_CMD: &[&str] = &["wrapping", "saturating", "checked"];
 static SET_CMD: &[&str] = &[
     "general_color",
     "greeting_color",
@@ -526,6 +742,7 @@ static SET_CMD: &[&str] = &[
     "prompt",
     "prompt_color",
     "float_mode",
+    "recursion_limit",
 ];
 static CMD_COLOR: &[&str] = &[
     "black", "purple", "cyan", "blue", "red", "yellow", "green", "white",
@@ -568,6 +785,43 @@ impl<Term: Terminal> Completer<Term> for CalcCompleter {
                 }
             },
 
+            Some("toggle_complex") => {
+                let mut co = Vec::new();
+                for cmd in TOGGLE_COMPLEX_CMD {
+                    if cmd.starts_with(word) {
+                        co.push(Completion::simple(cmd.to_string()));
+                    }
+                }
+
+                Some(co)
+            }
+
+            Some("toggle_radix") => {
+                let mut co = Vec::new();
+                for cmd in TOGGLE_RADIX_CMD {
+                    if cmd.starts_with(word) {
+                        co.push(Completion::simple(cmd.to_string()));
+                    }
+                }
+
+                Some(co)
+            }
+
+            Some("toggle_programmer") => {
+                let mut co = Vec::new();
+                let cmds = match words.next() {
+                    None => TOGGLE_PROGRAMMER_CMD,
+                    Some(_) => OVERFLOW_CMD,
+                };
+                for cmd in cmds {
+                    if cmd.starts_with(word) {
+                        co.push(Completion::simple(cmd.to_string()));
+                    }
+                }
+
+                Some(co)
+            }
+
             Some("config") => match words.next() {
                 None => {
                     let mut co = Vec::new();
@@ -2,27 +2,36 @@ use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 
 use ansi_term::Color;
+use num_bigint::BigInt;
+use num_complex::Complex64;
+use num_traits::Signed;
 
 use crate::exact_math::float_mode::FloatMode;
+use crate::exact_math::programmer_mode::ProgrammerMode;
 use crate::exact_math::rationals::Rationals;
 use crate::exact_math::scientific_mode::from_float;
 use crate::lexing::token::{Operator, Token};
 use crate::parsing::ast::Ast::{Nil, Node};
 use crate::parsing::ast::Parameters::*;
+use crate::utils::complex_utils::complex_to_string;
+use crate::utils::integer_utils::int_to_string;
 use crate::utils::matrix_utils::transpose;
 use crate::FLOAT_MODE;
+use crate::PROGRAMMER_MODE;
 
 pub type Ram = HashMap<String, Parameters>;
-pub type Functions = HashMap<String, (Vec<Ast>, Ast)>;
+pub type Functions = HashMap<String, Vec<Equation>>;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Parameters {
     Int(i64),
+    Big(BigInt),
     Float(f64),
     Bool(bool),
     Str(String),
     Identifier(String),
     Rational(Rationals),
+    Complex(Complex64),
     PlusOperation,
     MinusOperation,
     MultiplicationOperation,
@@ -33,9 +42,28 @@ pub enum Parameters {
     GreaterOperation,
     OrOperation,
     AndOperation,
+    BitAndOperation,
+    BitOrOperation,
+    XorOperation,
+    BitNotOperation,
+    ShiftLeftOperation,
+    ShiftRightOperation,
+    ModOperation,
+    FloorDivideOperation,
     Equal,
+    NotEqual,
     Not,
     Assign,
+    /// Applies its operator to a variable which must already be bound, `x +=
+    /// 1` is `x = x + 1`.
+    CompoundAssign(Box<Parameters>),
+    /// Guards the definition of a function, `sign(x) when x < 0 = -1`.
+    When,
+    Delete(String),
+    Memo(String),
+    /// The last parameter of a function, `...args`, which is bound to the
+    /// vector of the remaining arguments.
+    Variadic(String),
     Null,
     ExpoOperation,
     Vector(Box<Vec<Ast>>),
@@ -45,6 +73,28 @@ pub enum Parameters {
     Mul(Box<Parameters>, Box<Parameters>),
     Div(Box<Parameters>, Box<Parameters>),
     Call(String, Box<Parameters>),
+    Lambda(Box<Lambda>),
+}
+
+/// An anonymous function such as `x -> x^2`. The values of the local
+/// variables its body refers to are captured when it is created, globals are
+/// looked up when it is called.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lambda {
+    pub params: Vec<String>,
+    pub body: Ast,
+    pub captured: Ram,
+}
+
+/// One of the equations defining a user function, they are tried in order
+/// until one matches the arguments. Parameters which are not variables are
+/// patterns the arguments must be equal to, the guard is `Nil` when there is
+/// none.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Equation {
+    pub params: Vec<Ast>,
+    pub guard: Ast,
+    pub body: Ast,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -59,6 +109,24 @@ pub enum Ast {
         name: String,
         lst: Vec<Ast>,
     },
+    /// Only the branch selected by the condition is interpreted.
+    If {
+        condition: Box<Ast>,
+        then: Box<Ast>,
+        otherwise: Box<Ast>,
+    },
+    /// Statements evaluated in order, the value is the one of the last
+    /// statement.
+    Block(Vec<Ast>),
+    For {
+        variable: String,
+        iterable: Box<Ast>,
+        body: Box<Ast>,
+    },
+    While {
+        condition: Box<Ast>,
+        body: Box<Ast>,
+    },
 }
 
 pub fn int_to_superscript_string(i: i64) -> String {
@@ -99,7 +167,8 @@ pub fn int_to_superscript_string(i: i64) -> String {
 impl Display for Parameters {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Int(i) => write!(f, "{}", i),
+            Int(i) => write!(f, "{}", int_to_string(&BigInt::from(*i))),
+            Big(i) => write!(f, "{}", int_to_string(i)),
             Float(fs) => FLOAT_MODE.with(|fm| match *fm.borrow() {
                 FloatMode::Normal => write!(f, "{:.10}", fs),
                 FloatMode::Exact => write!(f, "{}", fs),
@@ -111,6 +180,11 @@ impl Display for Parameters {
             MultiplicationOperation => write!(f, "*"),
             DivideOperation => write!(f, "/"),
             Assign => write!(f, "="),
+            CompoundAssign(op) => write!(f, "{op}="),
+            When => write!(f, "when"),
+            Delete(s) => write!(f, "del {s}"),
+            Memo(s) => write!(f, "memo {s}"),
+            Variadic(s) => write!(f, "...{s}"),
             Null => write!(f, ""),
             ExpoOperation => write!(f, "^"),
             GreaterOperation => write!(f, ">"),
@@ -118,19 +192,113 @@ impl Display for Parameters {
             GreaterOrEqualOperation => write!(f, ">="),
             LesserOrEqualOperation => write!(f, "<="),
             Equal => write!(f, "=="),
+            NotEqual => write!(f, "!="),
             Not => write!(f, "!"),
             Bool(b) => write!(f, "{b}"),
             AndOperation => write!(f, "&&"),
+            BitAndOperation => write!(f, "&"),
+            BitOrOperation => write!(f, "|"),
+            XorOperation => write!(f, "xor"),
+            BitNotOperation => write!(f, "~"),
+            ShiftLeftOperation => write!(f, "<<"),
+            ShiftRightOperation => write!(f, ">>"),
+            ModOperation => write!(f, "mod"),
+            FloorDivideOperation => write!(f, "//"),
             OrOperation => write!(f, "||"),
             Vector(a) => write!(f, "{:?}", a),
             InterpreterVector(a) => write!(f, "{:?}", a),
             Str(s) => write!(f, "{s}"),
             Rational(s) => write!(f, "{s}"),
+            Complex(c) => write!(f, "{}", complex_to_string(c)),
             Plus(x, y) => write!(f, "(({x})+({y}))"),
             Mul(x, y) => write!(f, "(({x})*({y}))"),
             Var(x, y, s) => write!(f, "({x}){s}{}", int_to_superscript_string(*y)),
             Div(x, y) => write!(f, "(({x})/({y}))"),
             Call(x, y) => write!(f, "{x}({y})"),
+            Lambda(l) => write!(f, "{l}"),
+        }
+    }
+}
+
+impl Display for Lambda {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.params.as_slice() {
+            [param] => write!(f, "{param} -> {}", self.body),
+            params => write!(f, "({}) -> {}", params.join(", "), self.body),
+        }
+    }
+}
+
+impl Equation {
+    /// An equation without patterns nor guard.
+    pub fn new(params: Vec<Ast>, body: Ast) -> Self {
+        Equation {
+            params,
+            guard: Nil,
+            body,
+        }
+    }
+
+    /// The left side of the equation, `sign(x) when x < 0`.
+    pub fn head(&self, name: &str) -> String {
+        let call = Ast::Call {
+            name: name.to_string(),
+            lst: self.params.clone(),
+        };
+        match self.guard {
+            Nil => call.to_string(),
+            _ => format!("{call} when {}", self.guard),
+        }
+    }
+
+    /// Whether both equations cover the same arguments, whatever the names of
+    /// their variables, a new equation replaces such an equation instead of
+    /// being tried after it.
+    pub fn same_case(&self, other: &Equation) -> bool {
+        self.params.len() == other.params.len()
+            && self.guard == other.guard
+            && self.params.iter().zip(&other.params).all(|(a, b)| {
+                a == b || (a.parameter_name().is_some() && b.parameter_name().is_some())
+            })
+    }
+}
+
+impl Ast {
+    /// A lone identifier such as the `x` of `f(x)`.
+    pub fn is_variable(&self) -> bool {
+        match self {
+            Node {
+                value: Identifier(_),
+                left,
+                right,
+            } => **left == Nil && **right == Nil,
+            _ => false,
+        }
+    }
+
+    /// The name a parameter binds, unless it is a pattern: `x`, `n=2` or
+    /// `...args`.
+    pub fn parameter_name(&self) -> Option<&str> {
+        match self {
+            Node {
+                value: Identifier(s),
+                ..
+            } if self.is_variable() => Some(s),
+            Node {
+                value: Assign,
+                left,
+                ..
+            } => match &**left {
+                Node {
+                    value: Identifier(s),
+                    ..
+                } if left.is_variable() => Some(s),
+                _ => None,
+            },
+            Node {
+                value: Variadic(s), ..
+            } => Some(s),
+            _ => None,
         }
     }
 }
@@ -151,6 +319,24 @@ impl Display for Ast {
                 s.iter().for_each(|x1| vs.push(x1.to_string()));
                 write!(f, "{}({})", v, vs.join(",").to_string())
             }
+            Ast::If {
+                condition,
+                then,
+                otherwise,
+            } => match **otherwise {
+                Nil => write!(f, "if {condition} then {then}"),
+                _ => write!(f, "if {condition} then {then} else {otherwise}"),
+            },
+            Ast::Block(statements) => {
+                let vs: Vec<String> = statements.iter().map(|x| x.to_string()).collect();
+                write!(f, "{{{}}}", vs.join("; "))
+            }
+            Ast::For {
+                variable,
+                iterable,
+                body,
+            } => write!(f, "for {variable} in {iterable} {body}"),
+            Ast::While { condition, body } => write!(f, "while {condition} {body}"),
         }
     }
 }
@@ -213,7 +399,7 @@ impl Parameters {
                                 ""
                             }
                         }
-                        Rational(r) if r.clone() == Rationals::new(1, 1) => {
+                        Rational(ref r) if *r == Rationals::new(1, 1) => {
                             if division {
                                 "1"
                             } else {
@@ -234,7 +420,7 @@ impl Parameters {
                                 ""
                             }
                         }
-                        Rational(r) if r.clone() == Rationals::new(-1, 1) => {
+                        Rational(ref r) if *r == Rationals::new(-1, 1) => {
                             if division {
                                 "-1"
                             } else {
@@ -326,7 +512,7 @@ impl Parameters {
                  */
                 let mut matrix = false;
                 if vec.len() == 0 {
-                    return format!("");
+                    return "[]".to_string();
                 }
                 match lst.first().unwrap() {
                     Parameters::InterpreterVector(_) => matrix = true,
@@ -407,15 +593,24 @@ impl Parameters {
     pub fn argument_print(
         &self,
         ram: Option<&mut HashMap<String, Parameters>>,
-        function: Option<&mut HashMap<String, (Vec<Ast>, Ast)>>,
+        function: Option<&mut Functions>,
     ) -> String {
         match self.clone() {
-            Int(_) => format!(
-                "{}: {} = {}",
-                Color::Cyan.paint("val"),
-                Color::Green.paint("int"),
-                Color::Green.paint(self.pretty_print(ram, function))
-            ),
+            Int(i) => Big(BigInt::from(i)).argument_print(ram, function),
+            Big(b) => match PROGRAMMER_MODE.with(|pm| pm.borrow().clone()) {
+                ProgrammerMode::Fixed(t, _) => format!(
+                    "{}: {} = {}",
+                    Color::Cyan.paint("val"),
+                    Color::Green.paint(t.to_string()),
+                    Color::Green.paint(t.describe(&b))
+                ),
+                ProgrammerMode::Off => format!(
+                    "{}: {} = {}",
+                    Color::Cyan.paint("val"),
+                    Color::Green.paint("int"),
+                    Color::Green.paint(self.pretty_print(ram, function))
+                ),
+            },
             Float(_) => {
                 let val = self.pretty_print(ram, function);
                 if val.contains("/") {
@@ -452,6 +647,12 @@ impl Parameters {
                 Color::RGB(237, 138, 35).paint("rational"),
                 Color::RGB(237, 138, 35).paint(self.pretty_print(ram, function)),
             ),
+            Complex(_) => format!(
+                "{}: {} = {}",
+                Color::Cyan.paint("val"),
+                Color::RGB(163, 148, 232).paint("complex"),
+                Color::RGB(163, 148, 232).paint(self.pretty_print(ram, function)),
+            ),
             Bool(_) => format!(
                 "{}: {} = {}",
                 Color::Cyan.paint("val"),
@@ -482,6 +683,14 @@ impl Parameters {
                     Color::Red.paint(self.pretty_print(ram, function))
                 )
             }
+            Lambda(_) => {
+                format!(
+                    "{}: {} = {}",
+                    Color::Cyan.paint("val"),
+                    Color::RGB(255, 215, 0).paint("fun"),
+                    Color::RGB(255, 215, 0).paint(self.pretty_print(ram, function))
+                )
+            }
             Str(_) => {
                 format!(
                     "{}: {} = {}{}{}",
@@ -501,7 +710,9 @@ impl Parameters {
 pub fn token_to_parameter(token: Token) -> Parameters {
     match token {
         Token::INT(i) => Int(i),
+        Token::BIGINT(i) => Big(i),
         Token::FLOAT(f) => Float(f),
+        Token::IMAGINARY(f) => Complex(Complex64::new(0.0, f)),
         Token::IDENTIFIER(s) => Identifier(s),
         Token::OPE(Operator::PLUS) => PlusOperation,
         Token::OPE(Operator::MINUS) => MinusOperation,
@@ -509,6 +720,7 @@ pub fn token_to_parameter(token: Token) -> Parameters {
         Token::OPE(Operator::DIVIDE) => DivideOperation,
         Token::OPE(Operator::EXPO) => ExpoOperation,
         Token::OPE(Operator::EQUALITY) => Equal,
+        Token::OPE(Operator::NotEqual) => NotEqual,
         Token::OPE(Operator::GreaterOrEqual) => GreaterOrEqualOperation,
         Token::OPE(Operator::GreaterThan) => GreaterOperation,
         Token::OPE(Operator::LesserThan) => LesserOperation,
@@ -516,7 +728,16 @@ pub fn token_to_parameter(token: Token) -> Parameters {
         Token::OPE(Operator::NOT) => Not,
         Token::OPE(Operator::Or) => OrOperation,
         Token::OPE(Operator::And) => AndOperation,
+        Token::OPE(Operator::BitAnd) => BitAndOperation,
+        Token::OPE(Operator::BitOr) => BitOrOperation,
+        Token::OPE(Operator::Xor) => XorOperation,
+        Token::OPE(Operator::BitNot) => BitNotOperation,
+        Token::OPE(Operator::ShiftLeft) => ShiftLeftOperation,
+        Token::OPE(Operator::ShiftRight) => ShiftRightOperation,
+        Token::OPE(Operator::Mod) | Token::PERCENT => ModOperation,
+        Token::OPE(Operator::FloorDivide) => FloorDivideOperation,
         Token::EQUAL => Assign,
+        Token::WHEN => When,
         Token::BOOL(b) => Bool(b),
         Token::RBRACKET => Vector(Box::from(Vec::new())),
         _ => Null,
@@ -524,11 +745,18 @@ pub fn token_to_parameter(token: Token) -> Parameters {
 }
 
 impl Parameters {
+    /// Runtime errors are identifiers starting with `@`.
+    pub fn is_error(&self) -> bool {
+        matches!(self, Parameters::Identifier(s) if s.starts_with('@'))
+    }
+
     pub fn abs(self, ram: Option<&HashMap<String, Parameters>>) -> Parameters {
         match self {
             Parameters::Int(i) => Parameters::Int(i.abs()),
+            Parameters::Big(i) => Parameters::Big(i.abs()),
             Parameters::Float(f) => Parameters::Float(f.abs()),
             Parameters::Rational(r) => Parameters::Rational(r.abs()),
+            Parameters::Complex(c) => Parameters::Float(c.norm()),
             Parameters::Identifier(s) => match ram {
                 None => Parameters::Null,
                 Some(t) => {
@@ -546,6 +774,8 @@ impl Parameters {
 
 #[cfg(test)]
 mod test {
+    use std::collections::HashMap;
+
     use crate::parsing::ast::{Ast, Parameters};
 
     impl Ast {
@@ -567,4 +797,13 @@ mod test {
         let result = Ast::new(Parameters::Int(2));
         assert_eq!(result, expected)
     }
+
+    #[test]
+    pub fn test_print_empty_vector() {
+        let empty = Parameters::InterpreterVector(Box::from(Vec::new()));
+        assert_eq!(
+            empty.pretty_print(Some(&mut HashMap::new()), Some(&mut HashMap::new())),
+            "[]"
+        );
+    }
 }
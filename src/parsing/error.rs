@@ -0,0 +1,109 @@
+use std::fmt::{Display, Formatter};
+
+use ansi_term::Color;
+
+use crate::lexing::token::{Span, Token};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    UnknownCharacter(char),
+    UnexpectedToken(Token),
+    UnexpectedEnd,
+    Unclosed(char),
+    MalformedNumber,
+    NumberOutOfRange,
+    InvalidAssignmentTarget,
+    ChainedAssignment,
+    ExpectedArrow,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub span: Span,
+}
+
+impl ParseError {
+    pub fn new(kind: ParseErrorKind, span: Span) -> Self {
+        ParseError { kind, span }
+    }
+
+    /// Renders the error followed by the offending line and a caret under the
+    /// span, `input` must be the exact string which was lexed.
+    pub fn render(&self, input: &str) -> String {
+        let start = self.span.start.min(input.len());
+        let line_start = input[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[start..].find('\n').map_or(input.len(), |i| start + i);
+        let end = self.span.end.clamp(start, line_end);
+        let column = input[line_start..start].chars().count();
+        let width = input[start..end].chars().count().max(1);
+        format!(
+            "{} {}\n{}\n{}{}",
+            Color::Purple.paint("Error:"),
+            Color::Red.paint(self.to_string()),
+            &input[line_start..line_end],
+            " ".repeat(column),
+            Color::Red.bold().paint("^".repeat(width))
+        )
+    }
+}
+
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseErrorKind::UnknownCharacter(c) => write!(f, "unknown character '{c}'"),
+            ParseErrorKind::UnexpectedToken(t) => write!(f, "unexpected token '{t}'"),
+            ParseErrorKind::UnexpectedEnd => write!(f, "unexpected end of input"),
+            ParseErrorKind::MalformedNumber => write!(f, "malformed number literal"),
+            ParseErrorKind::NumberOutOfRange => write!(f, "number literal out of range"),
+            ParseErrorKind::InvalidAssignmentTarget => write!(f, "invalid assignment target"),
+            ParseErrorKind::ChainedAssignment => write!(f, "assignments cannot be chained"),
+            ParseErrorKind::ExpectedArrow => {
+                write!(f, "expected '->' after the parameters of a lambda")
+            }
+            ParseErrorKind::Unclosed('"') => write!(f, "unclosed quote"),
+            ParseErrorKind::Unclosed('[') => write!(f, "unclosed bracket '['"),
+            ParseErrorKind::Unclosed('{') => write!(f, "unclosed brace '{{'"),
+            ParseErrorKind::Unclosed(c) => write!(f, "unclosed parenthesis '{c}'"),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lexing::token::Span;
+
+    use super::{ParseError, ParseErrorKind};
+
+    #[test]
+    pub fn test_display() {
+        let error = ParseError::new(ParseErrorKind::UnknownCharacter('$'), Span::new(4, 5));
+        assert_eq!(error.to_string(), "unknown character '$'");
+    }
+
+    #[test]
+    pub fn test_render_caret() {
+        let error = ParseError::new(ParseErrorKind::UnknownCharacter('$'), Span::new(4, 5));
+        let rendered = error.render("1 + $");
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[1], "1 + $");
+        assert!(lines[2].starts_with("    "));
+        assert!(lines[2].contains('^'));
+    }
+
+    #[test]
+    pub fn test_render_multiline() {
+        let error = ParseError::new(ParseErrorKind::UnknownCharacter('$'), Span::new(8, 9));
+        let rendered = error.render("a = 1\nb $ 2\nc");
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[1], "b $ 2");
+        assert!(lines[2].starts_with("  "));
+        assert!(!lines[2].starts_with("   "));
+    }
+}
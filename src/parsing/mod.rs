@@ -1,3 +1,4 @@
 pub(crate) mod ast;
+pub(crate) mod error;
 pub mod parselets;
 pub(crate) mod parser;
@@ -1,6 +1,9 @@
+use std::collections::HashMap;
+
 use crate::lexing::token::{Precedence, Token, TokenType};
 use crate::parsing::ast::Ast::Call;
-use crate::parsing::ast::{token_to_parameter, Ast, Parameters};
+use crate::parsing::ast::{token_to_parameter, Ast, Lambda, Parameters};
+use crate::parsing::error::ParseErrorKind;
 use crate::parsing::parser::CalcParser;
 
 pub trait InfixParselet {
@@ -15,6 +18,16 @@ pub struct CallParselet {}
 
 pub struct NullParset {}
 
+pub struct ImplicitMultiplicationParselet {}
+
+pub struct ArrowParselet {}
+
+pub struct IndexParselet {}
+
+pub struct RangeParselet {}
+
+pub struct WhenParselet {}
+
 pub struct OperatorInfixParselet {
     pub is_right: bool,
     pub precedence: i64,
@@ -40,11 +53,98 @@ impl InfixParselet for OperatorInfixParselet {
     }
 }
 
+/// Variables and vectors of them, which are destructured, `[a, b] = [1, 2]`.
+fn is_pattern(ast: &Ast) -> bool {
+    match ast {
+        Ast::Node {
+            value: Parameters::Identifier(_),
+            left,
+            right,
+        } => **left == Ast::Nil && **right == Ast::Nil,
+        Ast::Node {
+            value: Parameters::Vector(targets),
+            ..
+        } => targets.iter().all(is_pattern),
+        _ => false,
+    }
+}
+
+/// Literals and operations on them, patterns the arguments of a function
+/// must be equal to, `fib(0)` or `sign(-1)`.
+fn is_constant(ast: &Ast) -> bool {
+    match ast {
+        Ast::Nil => true,
+        Ast::Node {
+            value: Parameters::Vector(values),
+            ..
+        } => values.iter().all(is_constant),
+        Ast::Node {
+            value:
+                Parameters::Int(_)
+                | Parameters::Float(_)
+                | Parameters::Bool(_)
+                | Parameters::Str(_)
+                | Parameters::PlusOperation
+                | Parameters::MinusOperation
+                | Parameters::MultiplicationOperation
+                | Parameters::DivideOperation
+                | Parameters::ExpoOperation,
+            left,
+            right,
+        } => is_constant(left) && is_constant(right),
+        _ => false,
+    }
+}
+
+/// Whether `left` is the head of an equation, a call written as such whose
+/// parameters are variables, patterns, `n=2` or `...args`. The calls
+/// desugared from `v[i]`, `a..b`, `f'(x)` or `n!` define nothing.
+fn is_definition(parser: &CalcParser, left: &Ast) -> bool {
+    match left {
+        Call { lst, .. } => {
+            parser.written_call()
+                && lst
+                    .iter()
+                    .all(|param| param.parameter_name().is_some() || is_constant(param))
+        }
+        _ => false,
+    }
+}
+
 impl InfixParselet for AssignParselet {
-    fn parse(&self, parser: &mut CalcParser, left: &Ast, _token: Token) -> Ast {
+    fn parse(&self, parser: &mut CalcParser, left: &Ast, token: Token) -> Ast {
+        let span = parser.last_span();
+        // a function is defined by `f(x) = ...` but cannot be updated
+        let valid = match (&token, left) {
+            (Token::EQUAL, Call { .. }) => is_definition(parser, left),
+            (
+                Token::EQUAL,
+                Ast::Node {
+                    value: Parameters::When,
+                    ..
+                },
+            ) => true,
+            _ => is_pattern(left),
+        };
+        if !valid {
+            parser.error(ParseErrorKind::InvalidAssignmentTarget, span);
+        }
         let right = parser.parse_expression_empty();
+        if let Ast::Node {
+            value: Parameters::Assign | Parameters::CompoundAssign(_),
+            ..
+        } = right
+        {
+            parser.error(ParseErrorKind::ChainedAssignment, span);
+        }
+        let value = match token {
+            Token::COMPOUND(op) => {
+                Parameters::CompoundAssign(Box::from(token_to_parameter(Token::OPE(op))))
+            }
+            _ => Parameters::Assign,
+        };
         Ast::Node {
-            value: Parameters::Assign,
+            value,
             left: Box::new(left.clone()),
             right: Box::new(right),
         }
@@ -55,34 +155,144 @@ impl InfixParselet for AssignParselet {
     }
 }
 
+/// `f(x)` calls `f`, any other callee, `(x -> x+1)(2)` or `add(1)(2)`, is a
+/// call to `apply` with the vector of the arguments.
 impl InfixParselet for CallParselet {
     fn parse(&self, parser: &mut CalcParser, left: &Ast, _token: Token) -> Ast {
+        let opening = parser.last_span();
         let name = match left {
-            Ast::Nil => "",
             Ast::Node {
-                value: v,
-                left: _left,
-                right: _right,
-            } => match v {
-                Parameters::Identifier(s) => s.as_str(),
-                _ => "",
-            },
-            _ => "",
+                value: Parameters::Identifier(s),
+                ..
+            } => Some(s.as_str()),
+            Ast::Node {
+                value: Parameters::Lambda(_),
+                ..
+            }
+            | Call { .. } => None,
+            _ => Some(""),
         };
 
         let mut lst: Vec<Ast> = Vec::new();
-        if !parser.match_token(TokenType::RPAR) {
+        if parser.match_token(TokenType::RPAR) {
+            parser.consume();
+        } else if parser.match_token(TokenType::Null) {
+            // `sin(` is unclosed rather than missing an argument
+            parser.consume_closing(TokenType::RPAR, '(', opening);
+        } else {
             lst.push(parser.parse_expression_empty());
             while parser.match_token(TokenType::COMMA) {
                 parser.consume();
                 let ast = parser.parse_expression_empty();
                 lst.push(ast);
             }
-            parser.consume_expected(TokenType::RPAR);
+            parser.consume_closing(TokenType::RPAR, '(', opening);
+        }
+        match name {
+            Some(name) => Call {
+                name: name.to_string(),
+                lst,
+            },
+            None => Call {
+                name: "apply".to_string(),
+                lst: vec![
+                    left.clone(),
+                    Ast::Node {
+                        value: Parameters::Vector(Box::from(lst)),
+                        left: Box::new(Ast::Nil),
+                        right: Box::new(Ast::Nil),
+                    },
+                ],
+            },
+        }
+    }
+
+    fn get_precedence(&self) -> i64 {
+        Precedence::CALL as i64
+    }
+}
+
+impl InfixParselet for ImplicitMultiplicationParselet {
+    fn parse(&self, parser: &mut CalcParser, left: &Ast, token: Token) -> Ast {
+        // the token already starts the right operand
+        let right = parser.parse_expression_from(token, self.get_precedence());
+        Ast::Node {
+            value: Parameters::MultiplicationOperation,
+            left: Box::new(left.clone()),
+            right: Box::new(right),
         }
+    }
+
+    fn get_precedence(&self) -> i64 {
+        Precedence::PRODUCT as i64
+    }
+}
+
+/// Parses the body of a lambda once its arrow is consumed, all of `params`
+/// must be plain identifiers.
+pub fn parse_lambda(parser: &mut CalcParser, params: Vec<Ast>, arrow: Token) -> Ast {
+    let mut names = Vec::new();
+    for param in params {
+        match param {
+            Ast::Node {
+                value: Parameters::Identifier(s),
+                ..
+            } => names.push(s),
+            _ => {
+                parser.unexpected(arrow);
+                return Ast::Nil;
+            }
+        }
+    }
+    let body = parser.parse_expression_empty();
+    Ast::Node {
+        value: Parameters::Lambda(Box::from(Lambda {
+            params: names,
+            body,
+            captured: HashMap::new(),
+        })),
+        left: Box::new(Ast::Nil),
+        right: Box::new(Ast::Nil),
+    }
+}
+
+impl InfixParselet for ArrowParselet {
+    fn parse(&self, parser: &mut CalcParser, left: &Ast, token: Token) -> Ast {
+        parse_lambda(parser, vec![left.clone()], token)
+    }
+
+    fn get_precedence(&self) -> i64 {
+        Precedence::ASSIGNMENT as i64
+    }
+}
+
+/// `v[i]` is a call to `index`, `v[i:j]` a call to `slice` where omitted
+/// bounds are `Nil`.
+impl InfixParselet for IndexParselet {
+    fn parse(&self, parser: &mut CalcParser, left: &Ast, _token: Token) -> Ast {
+        let opening = parser.last_span();
+        let start = if parser.match_token(TokenType::COLON) {
+            Ast::Nil
+        } else {
+            parser.parse_expression_empty()
+        };
+        if !parser.match_token(TokenType::COLON) {
+            parser.consume_closing(TokenType::RBRACKET, '[', opening);
+            return Call {
+                name: "index".to_string(),
+                lst: vec![left.clone(), start],
+            };
+        }
+        parser.consume();
+        let end = if parser.match_token(TokenType::RBRACKET) {
+            Ast::Nil
+        } else {
+            parser.parse_expression_empty()
+        };
+        parser.consume_closing(TokenType::RBRACKET, '[', opening);
         Call {
-            name: name.to_string(),
-            lst,
+            name: "slice".to_string(),
+            lst: vec![left.clone(), start, end],
         }
     }
 
@@ -91,6 +301,21 @@ impl InfixParselet for CallParselet {
     }
 }
 
+/// `a..b` is a call to `range`.
+impl InfixParselet for RangeParselet {
+    fn parse(&self, parser: &mut CalcParser, left: &Ast, _token: Token) -> Ast {
+        let right = parser.parse_expression(self.get_precedence());
+        Call {
+            name: "range".to_string(),
+            lst: vec![left.clone(), right],
+        }
+    }
+
+    fn get_precedence(&self) -> i64 {
+        Precedence::RANGE as i64
+    }
+}
+
 impl InfixParselet for NullParset {
     fn parse(&self, _parser: &mut CalcParser, left: &Ast, _token: Token) -> Ast {
         left.clone()
@@ -100,3 +325,28 @@ impl InfixParselet for NullParset {
         0
     }
 }
+
+/// `sign(x) when x < 0`, the guard of an equation defining a function. It
+/// must be followed by the `=` of the definition.
+impl InfixParselet for WhenParselet {
+    fn parse(&self, parser: &mut CalcParser, left: &Ast, token: Token) -> Ast {
+        let span = parser.last_span();
+        if !is_definition(parser, left) {
+            parser.error(ParseErrorKind::InvalidAssignmentTarget, span);
+        }
+        let guard = parser.parse_expression(self.get_precedence());
+        if !parser.match_token(TokenType::EQUAL) {
+            let token = parser.consume();
+            parser.unexpected(token);
+        }
+        Ast::Node {
+            value: token_to_parameter(token),
+            left: Box::new(left.clone()),
+            right: Box::new(guard),
+        }
+    }
+
+    fn get_precedence(&self) -> i64 {
+        Precedence::ASSIGNMENT as i64
+    }
+}
@@ -1,2 +1,3 @@
 pub mod infix_parselet;
+pub mod postfix_parselet;
 pub mod prefix_parselet;
@@ -0,0 +1,66 @@
+use crate::lexing::token::{Token, TokenType};
+use crate::parsing::ast::{Ast, Parameters};
+use crate::parsing::parselets::infix_parselet::{CallParselet, InfixParselet};
+use crate::parsing::parser::CalcParser;
+
+/// Operators written after their operand, they all bind with the
+/// `POSTFIX` precedence.
+pub trait PostfixParselet {
+    fn parse(&self, parser: &mut CalcParser, left: &Ast, token: Token) -> Ast;
+}
+
+pub struct FactorialParselet {}
+
+pub struct PercentParselet {}
+
+pub struct PrimeParselet {}
+
+impl PostfixParselet for FactorialParselet {
+    fn parse(&self, _parser: &mut CalcParser, left: &Ast, _token: Token) -> Ast {
+        Ast::Call {
+            name: "fact".to_string(),
+            lst: vec![left.clone()],
+        }
+    }
+}
+
+impl PostfixParselet for PercentParselet {
+    fn parse(&self, _parser: &mut CalcParser, left: &Ast, _token: Token) -> Ast {
+        Ast::Node {
+            value: Parameters::DivideOperation,
+            left: Box::new(left.clone()),
+            right: Box::new(Ast::Node {
+                value: Parameters::Int(100),
+                left: Box::new(Ast::Nil),
+                right: Box::new(Ast::Nil),
+            }),
+        }
+    }
+}
+
+impl PostfixParselet for PrimeParselet {
+    fn parse(&self, parser: &mut CalcParser, left: &Ast, _token: Token) -> Ast {
+        // f'(x) is diff(f, x), the arguments are parsed like a call
+        let diff = Ast::Node {
+            value: Parameters::Identifier("diff".to_string()),
+            left: Box::new(Ast::Nil),
+            right: Box::new(Ast::Nil),
+        };
+        let call = if parser.match_token(TokenType::LPAR) {
+            let token = parser.consume();
+            CallParselet {}.parse(parser, &diff, token)
+        } else {
+            Ast::Call {
+                name: "diff".to_string(),
+                lst: Vec::new(),
+            }
+        };
+        match call {
+            Ast::Call { name, mut lst } => {
+                lst.insert(0, left.clone());
+                Ast::Call { name, lst }
+            }
+            ast => ast,
+        }
+    }
+}
@@ -1,6 +1,11 @@
-use crate::lexing::token::{Token, TokenType};
-use crate::parsing::ast::{token_to_parameter, Ast};
+use num_bigint::BigInt;
+
+use crate::lexing::token::{Operator, Precedence, Span, Token, TokenType};
+use crate::parsing::ast::{token_to_parameter, Ast, Parameters};
+use crate::parsing::error::ParseErrorKind;
+use crate::parsing::parselets::infix_parselet::parse_lambda;
 use crate::parsing::parser::CalcParser;
+use crate::utils::integer_utils::from_big;
 
 pub trait PrefixParselet {
     fn parse(&self, parser: &mut CalcParser, token: Token) -> Ast;
@@ -24,6 +29,27 @@ pub struct VecParselet {}
 #[derive(Clone)]
 pub struct QuoteParselet {}
 
+#[derive(Clone)]
+pub struct IfParselet {}
+
+#[derive(Clone)]
+pub struct DelParselet {}
+
+#[derive(Clone)]
+pub struct MemoParselet {}
+
+#[derive(Clone)]
+pub struct VariadicParselet {}
+
+#[derive(Clone)]
+pub struct BlockParselet {}
+
+#[derive(Clone)]
+pub struct ForParselet {}
+
+#[derive(Clone)]
+pub struct WhileParselet {}
+
 impl PrefixParselet for ValueParselet {
     fn parse(&self, _parser: &mut CalcParser, token: Token) -> Ast {
         Ast::Node {
@@ -34,9 +60,35 @@ impl PrefixParselet for ValueParselet {
     }
 }
 
+/// `-128` is a literal rather than the opposite of `128`, so that it fits in
+/// an `i8` in programmer mode.
+fn negative_literal(operand: &Ast) -> Option<Ast> {
+    let value = match operand {
+        Ast::Node { value, left, right } if **left == Ast::Nil && **right == Ast::Nil => value,
+        _ => return None,
+    };
+    let value = match value {
+        Parameters::Int(i) => from_big(-BigInt::from(*i)),
+        Parameters::Big(i) => from_big(-i.clone()),
+        Parameters::Float(f) => Parameters::Float(-f),
+        _ => return None,
+    };
+    Some(Ast::Node {
+        value,
+        left: Box::from(Ast::Nil),
+        right: Box::from(Ast::Nil),
+    })
+}
+
 impl PrefixParselet for OperatorPrefixParselet {
     fn parse(&self, parser: &mut CalcParser, token: Token) -> Ast {
-        let operand = parser.parse_expression_empty();
+        // binds tighter than products but looser than powers, -2^2 is -(2^2)
+        let operand = parser.parse_expression(Precedence::PREFIX as i64);
+        if token == Token::OPE(Operator::MINUS) {
+            if let Some(literal) = negative_literal(&operand) {
+                return literal;
+            }
+        }
         Ast::Node {
             value: token_to_parameter(token),
             left: Box::from(operand),
@@ -46,21 +98,44 @@ impl PrefixParselet for OperatorPrefixParselet {
 }
 
 impl PrefixParselet for NullParselet {
-    fn parse(&self, _parser: &mut CalcParser, _token: Token) -> Ast {
+    fn parse(&self, parser: &mut CalcParser, token: Token) -> Ast {
+        parser.unexpected(token);
         Ast::Nil
     }
 }
 
 impl PrefixParselet for GroupParselet {
     fn parse(&self, parser: &mut CalcParser, _token: Token) -> Ast {
+        let opening = parser.last_span();
+        if parser.match_token(TokenType::Null) {
+            parser.consume_closing(TokenType::RPAR, '(', opening);
+            return Ast::Nil;
+        }
         let expression = parser.parse_expression_empty();
-        parser.consume_expected(TokenType::RPAR);
-        expression
+        if !parser.match_token(TokenType::COMMA) {
+            parser.consume_closing(TokenType::RPAR, '(', opening);
+            return expression;
+        }
+        // only the parameters of a lambda are separated by commas, (x, y) -> x*y
+        let mut params = vec![expression];
+        while parser.match_token(TokenType::COMMA) {
+            parser.consume();
+            params.push(parser.parse_expression_empty());
+        }
+        parser.consume_closing(TokenType::RPAR, '(', opening);
+        if !parser.match_token(TokenType::ARROW) {
+            let params = Span::new(opening.start, parser.last_span().end);
+            parser.error(ParseErrorKind::ExpectedArrow, params);
+            return Ast::Nil;
+        }
+        let arrow = parser.consume();
+        parse_lambda(parser, params, arrow)
     }
 }
 
 impl PrefixParselet for VecParselet {
     fn parse(&self, parser: &mut CalcParser, _token: Token) -> Ast {
+        let opening = parser.last_span();
         let mut vec: Vec<Ast> = Vec::new();
 
         if !parser.match_token(TokenType::RBRACKET) {
@@ -69,7 +144,9 @@ impl PrefixParselet for VecParselet {
                 parser.consume();
                 vec.push(parser.parse_expression_empty());
             }
-            parser.consume_expected(TokenType::RBRACKET);
+            parser.consume_closing(TokenType::RBRACKET, '[', opening);
+        } else {
+            parser.consume();
         }
 
         Ast::Node {
@@ -84,16 +161,14 @@ impl PrefixParselet for QuoteParselet {
     fn parse(&self, parser: &mut CalcParser, _token: Token) -> Ast {
         let mut str: String = String::new();
 
-        if !parser.match_token(TokenType::QUOTE) {
-            while !parser.match_token(TokenType::QUOTE) {
-                match parser.consume() {
-                    Token::IDENTIFIER(s) => str = str + &s.to_string(),
+        while !parser.match_token(TokenType::QUOTE) && !parser.match_token(TokenType::Null) {
+            match parser.consume() {
+                Token::IDENTIFIER(s) => str = str + &s.to_string(),
 
-                    t => str = str + &t.to_string(),
-                }
+                t => str = str + &t.to_string(),
             }
-            parser.consume_expected(TokenType::QUOTE);
         }
+        parser.consume_expected(TokenType::QUOTE);
 
         Ast::Node {
             value: crate::parsing::ast::Parameters::Str(str.trim().to_string()),
@@ -102,3 +177,115 @@ impl PrefixParselet for QuoteParselet {
         }
     }
 }
+
+impl PrefixParselet for IfParselet {
+    fn parse(&self, parser: &mut CalcParser, _token: Token) -> Ast {
+        let condition = parser.parse_expression_empty();
+        parser.consume_expected(TokenType::THEN);
+        let then = parser.parse_expression_empty();
+        let otherwise = if parser.match_token(TokenType::ELSE) {
+            parser.consume();
+            parser.parse_expression_empty()
+        } else {
+            Ast::Nil
+        };
+        Ast::If {
+            condition: Box::from(condition),
+            then: Box::from(then),
+            otherwise: Box::from(otherwise),
+        }
+    }
+}
+
+impl PrefixParselet for DelParselet {
+    fn parse(&self, parser: &mut CalcParser, _token: Token) -> Ast {
+        match parser.consume() {
+            Token::IDENTIFIER(name) => Ast::Node {
+                value: Parameters::Delete(name),
+                left: Box::from(Ast::Nil),
+                right: Box::from(Ast::Nil),
+            },
+            token => {
+                parser.unexpected(token);
+                Ast::Nil
+            }
+        }
+    }
+}
+
+impl PrefixParselet for MemoParselet {
+    fn parse(&self, parser: &mut CalcParser, _token: Token) -> Ast {
+        match parser.consume() {
+            Token::IDENTIFIER(name) => Ast::Node {
+                value: Parameters::Memo(name),
+                left: Box::from(Ast::Nil),
+                right: Box::from(Ast::Nil),
+            },
+            token => {
+                parser.unexpected(token);
+                Ast::Nil
+            }
+        }
+    }
+}
+
+impl PrefixParselet for VariadicParselet {
+    fn parse(&self, parser: &mut CalcParser, _token: Token) -> Ast {
+        match parser.consume() {
+            Token::IDENTIFIER(name) => Ast::Node {
+                value: Parameters::Variadic(name),
+                left: Box::from(Ast::Nil),
+                right: Box::from(Ast::Nil),
+            },
+            token => {
+                parser.unexpected(token);
+                Ast::Nil
+            }
+        }
+    }
+}
+
+impl PrefixParselet for BlockParselet {
+    fn parse(&self, parser: &mut CalcParser, _token: Token) -> Ast {
+        parser.parse_block()
+    }
+}
+
+/// The body of a loop must be a block, `for i in 1..n { s = s + i }`.
+fn parse_loop_body(parser: &mut CalcParser) -> Ast {
+    match parser.consume_expected(TokenType::LBRACE) {
+        Token::Null => Ast::Nil,
+        _ => parser.parse_block(),
+    }
+}
+
+impl PrefixParselet for ForParselet {
+    fn parse(&self, parser: &mut CalcParser, _token: Token) -> Ast {
+        let variable = match parser.consume() {
+            Token::IDENTIFIER(name) => name,
+            token => {
+                parser.unexpected(token);
+                return Ast::Nil;
+            }
+        };
+        parser.consume_expected(TokenType::IN);
+        let iterable = parser.parse_expression_empty();
+        let body = parse_loop_body(parser);
+        Ast::For {
+            variable,
+            iterable: Box::from(iterable),
+            body: Box::from(body),
+        }
+    }
+}
+
+impl PrefixParselet for WhileParselet {
+    fn parse(&self, parser: &mut CalcParser, _token: Token) -> Ast {
+        let condition = parser.parse_expression_empty();
+        let body = parse_loop_body(parser);
+        Ast::While {
+            condition: Box::from(condition),
+            body: Box::from(body),
+        }
+    }
+}
@@ -1,48 +1,141 @@
 use core::slice::Iter;
 
 use crate::lexing::token::Token::*;
-use crate::lexing::token::{Precedence, Token, TokenType};
-use crate::parsing::ast::Ast;
+use crate::lexing::token::{Precedence, Span, SpannedToken, Token, TokenType};
+use crate::parsing::ast::{Ast, Parameters};
+use crate::parsing::error::{ParseError, ParseErrorKind};
 use crate::parsing::parselets::infix_parselet::{
-    AssignParselet, CallParselet, InfixParselet, NullParset, OperatorInfixParselet,
+    ArrowParselet, AssignParselet, CallParselet, ImplicitMultiplicationParselet, IndexParselet,
+    InfixParselet, NullParset, OperatorInfixParselet, RangeParselet, WhenParselet,
+};
+use crate::parsing::parselets::postfix_parselet::{
+    FactorialParselet, PercentParselet, PostfixParselet, PrimeParselet,
 };
 use crate::parsing::parselets::prefix_parselet::{
     GroupParselet, NullParselet, OperatorPrefixParselet, PrefixParselet, ValueParselet,
 };
 
-use super::parselets::prefix_parselet::{QuoteParselet, VecParselet};
+use super::parselets::prefix_parselet::{
+    BlockParselet, DelParselet, ForParselet, IfParselet, MemoParselet, QuoteParselet,
+    VariadicParselet, VecParselet, WhileParselet,
+};
 
 #[derive(Clone)]
 pub struct CalcParser<'a> {
-    tokens: Iter<'a, Token>,
-    read: Vec<Token>,
+    tokens: Iter<'a, SpannedToken>,
+    read: Vec<SpannedToken>,
+    last_span: Span,
+    error: Option<ParseError>,
+    /// Whether the last operand was written as a call, `f(x)`, rather than
+    /// desugared into one like `v[i]` or `n!`, only the former defines a
+    /// function.
+    written_call: bool,
 }
 
-pub fn init_calc_parser(input: &Vec<Token>) -> CalcParser {
+pub fn init_calc_parser(input: &[SpannedToken]) -> CalcParser<'_> {
     CalcParser {
         tokens: input.iter(),
         read: Vec::new(),
+        last_span: Span::default(),
+        error: None,
+        written_call: false,
     }
 }
 
 impl CalcParser<'_> {
-    pub fn parse(&mut self) -> Ast {
-        self.parse_expression_empty()
+    /// Parses input made of exactly one expression.
+    #[cfg(test)]
+    pub fn parse(&mut self) -> Result<Ast, ParseError> {
+        if self.look_ahead(0) == Null {
+            return Ok(Ast::Nil);
+        }
+        let ast = self.parse_expression_empty();
+        if self.look_ahead(0) != Null {
+            let token = self.consume();
+            self.unexpected(token);
+        }
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => Ok(ast),
+        }
     }
+    /// Parses statements separated by `;` or new lines, empty statements are
+    /// skipped.
+    pub fn parse_statements(&mut self) -> Result<Vec<Ast>, ParseError> {
+        let mut statements = Vec::new();
+        loop {
+            while self.match_token(TokenType::SEMICOLON) {
+                self.consume();
+            }
+            if self.look_ahead(0) == Null {
+                break;
+            }
+            statements.push(self.parse_expression_empty());
+            if !self.match_token(TokenType::SEMICOLON) && self.look_ahead(0) != Null {
+                let token = self.consume();
+                self.unexpected(token);
+                break;
+            }
+        }
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => Ok(statements),
+        }
+    }
+
+    /// Parses the statements of a block up to its closing brace, the opening
+    /// brace being the last consumed token.
+    pub fn parse_block(&mut self) -> Ast {
+        let opening = self.last_span();
+        let mut statements = Vec::new();
+        loop {
+            while self.match_token(TokenType::SEMICOLON) {
+                self.consume();
+            }
+            if self.match_token(TokenType::RBRACE) || self.look_ahead(0) == Null {
+                break;
+            }
+            statements.push(self.parse_expression_empty());
+            if !self.match_token(TokenType::SEMICOLON) {
+                break;
+            }
+        }
+        self.consume_closing(TokenType::RBRACE, '{', opening);
+        Ast::Block(statements)
+    }
+
     pub fn parse_expression(&mut self, precedence: i64) -> Ast {
-        let mut token = self.consume();
+        let token = self.consume();
+        self.parse_expression_from(token, precedence)
+    }
+
+    /// Parses an expression whose first token was already consumed.
+    pub fn parse_expression_from(&mut self, mut token: Token, precedence: i64) -> Ast {
         let prefix = self
             .clone()
             .get_prefix_parselet(token.clone().to_token_type());
 
         let mut left = prefix.unwrap().parse(self, token.clone());
-        while precedence < self.get_precedence() {
+        self.written_call = false;
+        while precedence < self.get_precedence(&left) {
             token = self.consume();
-            let parser = self
-                .clone()
-                .get_infix_parselet(token.clone().to_token_type())
-                .unwrap();
-            left = parser.parse(self, &left, token);
+            let token_type = token.clone().to_token_type();
+            let written_call = token_type == TokenType::LPAR
+                && matches!(
+                    left,
+                    Ast::Node {
+                        value: Parameters::Identifier(_),
+                        ..
+                    }
+                );
+            left = match self.postfix_parselet(token_type.clone(), 0) {
+                Some(postfix) => postfix.parse(self, &left, token),
+                None => {
+                    let parser = self.infix_parselet(&left, token_type).unwrap();
+                    parser.parse(self, &left, token)
+                }
+            };
+            self.written_call = written_call;
         }
         left
     }
@@ -59,52 +152,145 @@ impl CalcParser<'_> {
         }
         match self.read.get(distance) {
             None => Null,
-            Some(t) => t.clone(),
+            Some(t) => t.token.clone(),
         }
     }
     pub fn consume(&mut self) -> Token {
         self.look_ahead(0);
-        if self.read.len() == 0 {
+        if self.read.is_empty() {
+            self.last_span = Span::new(self.last_span.end, self.last_span.end);
             return Null;
         }
-        self.read.remove(0)
+        let t = self.read.remove(0);
+        self.last_span = t.span;
+        t.token
+    }
+
+    /// Span of the last consumed token, at the end of the input it is the
+    /// empty span right after the last token.
+    pub fn last_span(&self) -> Span {
+        self.last_span
+    }
+
+    pub fn written_call(&self) -> bool {
+        self.written_call
+    }
+
+    /// Records an error, only the first one is reported by `parse`.
+    pub fn error(&mut self, kind: ParseErrorKind, span: Span) {
+        if self.error.is_none() {
+            self.error = Some(ParseError::new(kind, span));
+        }
+    }
+
+    /// Records an error for the last consumed token.
+    pub fn unexpected(&mut self, token: Token) {
+        let kind = match token {
+            Null => ParseErrorKind::UnexpectedEnd,
+            t => ParseErrorKind::UnexpectedToken(t),
+        };
+        self.error(kind, self.last_span);
     }
 
     pub fn match_token(&mut self, expected: TokenType) -> bool {
         let token = self.look_ahead(0);
-        if token.to_token_type() != expected {
-            return false;
-        }
-        return true;
+        token.to_token_type() == expected
     }
 
     pub fn consume_expected(&mut self, expected: TokenType) -> Token {
-        self.look_ahead(0);
-        if self.read.len() == 0 {
-            return Null;
+        let t = self.consume();
+        if t.to_token_type() == expected {
+            t
+        } else {
+            self.unexpected(t);
+            Null
         }
-        match self.read.remove(0) {
-            t => {
-                if t.to_token_type() == expected {
-                    t
-                } else {
-                    println!("error!");
-                    Null
-                }
-            }
+    }
+
+    /// Consumes the token closing the delimiter `opening` which was opened at
+    /// `span`, reporting the delimiter as unclosed if the input ends first.
+    pub fn consume_closing(&mut self, expected: TokenType, opening: char, span: Span) -> Token {
+        if self.look_ahead(0) == Null {
+            self.error(ParseErrorKind::Unclosed(opening), span);
+            return Null;
         }
+        self.consume_expected(expected)
     }
 
-    fn get_precedence(&mut self) -> i64 {
-        let p: Option<Box<dyn InfixParselet>> = self
-            .clone()
-            .get_infix_parselet(self.look_ahead(0).to_token_type());
+    fn get_precedence(&mut self, left: &Ast) -> i64 {
+        let token_type = self.look_ahead(0).to_token_type();
+        if self.postfix_parselet(token_type.clone(), 1).is_some() {
+            return Precedence::POSTFIX as i64;
+        }
+        let p: Option<Box<dyn InfixParselet>> = self.infix_parselet(left, token_type);
         match p {
             None => 0,
             Some(t) => (*t).get_precedence(),
         }
     }
 
+    /// Whether the tokens at `distance` and right after it are not separated
+    /// by any space.
+    fn adjacent(&mut self, distance: usize) -> bool {
+        self.look_ahead(distance + 1);
+        match (self.read.get(distance), self.read.get(distance + 1)) {
+            (Some(a), Some(b)) => a.span.end == b.span.start,
+            _ => false,
+        }
+    }
+
+    /// `%` is a modulo when an operand follows it, `7 % 3` or `7 % -3`, and a
+    /// percent otherwise, `50%` or `50% - 3`: a sign only starts an operand
+    /// when nothing separates them. `next` is the distance of the token after
+    /// the operator.
+    fn postfix_parselet(
+        &mut self,
+        token_type: TokenType,
+        next: usize,
+    ) -> Option<Box<dyn PostfixParselet>> {
+        let operand_follows = match self.look_ahead(next).to_token_type() {
+            TokenType::INT
+            | TokenType::FLOAT
+            | TokenType::IDENTIFIER
+            | TokenType::BOOL
+            | TokenType::LPAR
+            | TokenType::LBRACKET
+            | TokenType::LBRACE
+            | TokenType::QUOTE
+            | TokenType::IF
+            | TokenType::NOT
+            | TokenType::BITNOT => true,
+            TokenType::PLUS | TokenType::MINUS => self.adjacent(next),
+            _ => false,
+        };
+        if token_type == TokenType::PERCENT && operand_follows {
+            return None;
+        }
+        self.clone().get_postfix_parselet(token_type)
+    }
+
+    /// An identifier or a parenthesis right after an operand is an implicit
+    /// multiplication, `2x` or `(a+b)(a-b)`, except for calls: `f(x)`, a
+    /// lambda `(x -> x+1)(2)` or the function a call returns `add(1)(2)`.
+    fn infix_parselet(
+        &mut self,
+        left: &Ast,
+        token_type: TokenType,
+    ) -> Option<Box<dyn InfixParselet>> {
+        let is_callee = matches!(
+            left,
+            Ast::Node {
+                value: Parameters::Identifier(_) | Parameters::Lambda(_),
+                ..
+            } | Ast::Call { .. }
+        );
+        match token_type {
+            TokenType::IDENTIFIER => Some(Box::from(ImplicitMultiplicationParselet {})),
+            TokenType::LPAR if !is_callee => Some(Box::from(ImplicitMultiplicationParselet {})),
+            t => self.clone().get_infix_parselet(t),
+        }
+    }
+
     pub fn get_infix_parselet(self, token_type: TokenType) -> Option<Box<dyn InfixParselet>> {
         match token_type {
             TokenType::PLUS => Some(Box::from(OperatorInfixParselet {
@@ -113,7 +299,7 @@ impl CalcParser<'_> {
             })),
             TokenType::MINUS => Some(Box::from(OperatorInfixParselet {
                 is_right: false,
-                precedence: (Precedence::MINUS as i64),
+                precedence: (Precedence::SUM as i64),
             })),
             TokenType::MULTIPLICATION => Some(Box::from(OperatorInfixParselet {
                 is_right: false,
@@ -121,19 +307,31 @@ impl CalcParser<'_> {
             })),
             TokenType::DIVIDE => Some(Box::from(OperatorInfixParselet {
                 is_right: false,
-                precedence: (Precedence::DIVIDE as i64),
+                precedence: (Precedence::PRODUCT as i64),
             })),
-            TokenType::EQUAL => Some(Box::from(AssignParselet {})),
-            TokenType::EXPO => Some(Box::from(OperatorInfixParselet {
+            TokenType::FLOORDIVIDE => Some(Box::from(OperatorInfixParselet {
                 is_right: false,
+                precedence: (Precedence::PRODUCT as i64),
+            })),
+            TokenType::MOD | TokenType::PERCENT => Some(Box::from(OperatorInfixParselet {
+                is_right: false,
+                precedence: (Precedence::PRODUCT as i64),
+            })),
+            TokenType::EQUAL | TokenType::COMPOUND => Some(Box::from(AssignParselet {})),
+            TokenType::EXPO => Some(Box::from(OperatorInfixParselet {
+                is_right: true,
                 precedence: (Precedence::EXPONENT as i64),
             })),
             TokenType::LPAR => Some(Box::from(CallParselet {})),
-            TokenType::NOT => Some(Box::from(OperatorInfixParselet {
+            TokenType::ARROW => Some(Box::from(ArrowParselet {})),
+            TokenType::LBRACKET => Some(Box::from(IndexParselet {})),
+            TokenType::RANGE => Some(Box::from(RangeParselet {})),
+            TokenType::WHEN => Some(Box::from(WhenParselet {})),
+            TokenType::EQUALITY => Some(Box::from(OperatorInfixParselet {
                 is_right: false,
                 precedence: (Precedence::CONDITIONAL as i64),
             })),
-            TokenType::EQUALITY => Some(Box::from(OperatorInfixParselet {
+            TokenType::NOTEQUAL => Some(Box::from(OperatorInfixParselet {
                 is_right: false,
                 precedence: (Precedence::CONDITIONAL as i64),
             })),
@@ -161,22 +359,50 @@ impl CalcParser<'_> {
                 is_right: false,
                 precedence: (Precedence::CONDITIONAL as i64),
             })),
+            TokenType::BITOR => Some(Box::from(OperatorInfixParselet {
+                is_right: false,
+                precedence: (Precedence::BITOR as i64),
+            })),
+            TokenType::XOR => Some(Box::from(OperatorInfixParselet {
+                is_right: false,
+                precedence: (Precedence::XOR as i64),
+            })),
+            TokenType::BITAND => Some(Box::from(OperatorInfixParselet {
+                is_right: false,
+                precedence: (Precedence::BITAND as i64),
+            })),
+            TokenType::SHIFTLEFT => Some(Box::from(OperatorInfixParselet {
+                is_right: false,
+                precedence: (Precedence::SHIFT as i64),
+            })),
+            TokenType::SHIFTRIGHT => Some(Box::from(OperatorInfixParselet {
+                is_right: false,
+                precedence: (Precedence::SHIFT as i64),
+            })),
             _ => Some(Box::from(NullParset {})),
         }
     }
 
+    pub fn get_postfix_parselet(self, token_type: TokenType) -> Option<Box<dyn PostfixParselet>> {
+        match token_type {
+            TokenType::NOT => Some(Box::from(FactorialParselet {})),
+            TokenType::PERCENT => Some(Box::from(PercentParselet {})),
+            TokenType::PRIME => Some(Box::from(PrimeParselet {})),
+            _ => None,
+        }
+    }
+
     pub fn get_prefix_parselet(self, token_type: TokenType) -> Option<Box<dyn PrefixParselet>> {
         match token_type {
             TokenType::PLUS => Some(Box::from(OperatorPrefixParselet {})),
             TokenType::MINUS => Some(Box::from(OperatorPrefixParselet {})),
-            TokenType::MULTIPLICATION => Some(Box::from(OperatorPrefixParselet {})),
-            TokenType::DIVIDE => Some(Box::from(OperatorPrefixParselet {})),
             TokenType::IDENTIFIER => Some(Box::from(ValueParselet {})),
             TokenType::INT => Some(Box::from(ValueParselet {})),
             TokenType::FLOAT => Some(Box::from(ValueParselet {})),
             TokenType::BOOL => Some(Box::from(ValueParselet {})),
             TokenType::LPAR => Some(Box::from(GroupParselet {})),
             TokenType::NOT => Some(Box::from(OperatorPrefixParselet {})),
+            TokenType::BITNOT => Some(Box::from(OperatorPrefixParselet {})),
             TokenType::EQUALITY => Some(Box::from(OperatorPrefixParselet {})),
             TokenType::LESSER => Some(Box::from(OperatorPrefixParselet {})),
             TokenType::LESSEREQ => Some(Box::from(OperatorPrefixParselet {})),
@@ -184,6 +410,13 @@ impl CalcParser<'_> {
             TokenType::GREATEREQ => Some(Box::from(OperatorPrefixParselet {})),
             TokenType::LBRACKET => Some(Box::from(VecParselet {})),
             TokenType::QUOTE => Some(Box::from(QuoteParselet {})),
+            TokenType::IF => Some(Box::from(IfParselet {})),
+            TokenType::DEL => Some(Box::from(DelParselet {})),
+            TokenType::MEMO => Some(Box::from(MemoParselet {})),
+            TokenType::ELLIPSIS => Some(Box::from(VariadicParselet {})),
+            TokenType::LBRACE => Some(Box::from(BlockParselet {})),
+            TokenType::FOR => Some(Box::from(ForParselet {})),
+            TokenType::WHILE => Some(Box::from(WhileParselet {})),
             _ => Some(Box::from(NullParselet {})),
         }
     }
@@ -191,25 +424,29 @@ impl CalcParser<'_> {
 
 #[cfg(test)]
 mod test {
+    use std::collections::HashMap;
+
     use crate::lexing::lexer::lex;
+    use crate::lexing::token::{Operator, Span, Token};
     use crate::parsing::ast::Parameters::{
         DivideOperation, MultiplicationOperation, PlusOperation,
     };
-    use crate::parsing::ast::{Ast, Parameters};
+    use crate::parsing::ast::{Ast, Lambda, Parameters};
+    use crate::parsing::error::{ParseError, ParseErrorKind};
     use crate::parsing::parser::{init_calc_parser, CalcParser};
 
     #[test]
     pub fn test_parse_nil() {
-        let b = lex("".to_string());
+        let b = lex("".to_string()).unwrap();
         let parser: &mut CalcParser = &mut init_calc_parser(&b);
         let expected = Ast::Nil;
-        let result = parser.parse();
+        let result = parser.parse().unwrap();
         assert_eq!(result, expected);
     }
 
     #[test]
     pub fn test_parse_one_token() {
-        let b = lex("2".to_string());
+        let b = lex("2".to_string()).unwrap();
         let parser: &mut CalcParser = &mut init_calc_parser(&b);
         let expected = Ast::Node {
             value: Parameters::Int(2),
@@ -217,26 +454,26 @@ mod test {
             right: Box::new(Ast::Nil),
         };
 
-        let result = parser.parse();
+        let result = parser.parse().unwrap();
         assert_eq!(result, expected);
     }
 
     #[test]
     pub fn test_parse_plus_operation() {
-        let b = lex("2+2".to_string());
+        let b = lex("2+2".to_string()).unwrap();
         let parser: &mut CalcParser = &mut init_calc_parser(&b);
         let expected = Ast::Node {
             value: Parameters::PlusOperation,
             left: Box::new(Ast::new(Parameters::Int(2))),
             right: Box::new(Ast::new(Parameters::Int(2))),
         };
-        let result = parser.parse();
+        let result = parser.parse().unwrap();
         assert_eq!(result, expected)
     }
 
     #[test]
     pub fn test_parse_plus_operation_hard() {
-        let b = lex("1+1+1".to_string());
+        let b = lex("1+1+1".to_string()).unwrap();
         let parser: &mut CalcParser = &mut init_calc_parser(&b);
         let expected = Ast::Node {
             value: Parameters::PlusOperation,
@@ -247,39 +484,39 @@ mod test {
             }),
             right: Box::from(Ast::new(Parameters::Int(1))),
         };
-        let result = parser.parse();
+        let result = parser.parse().unwrap();
         assert_eq!(result, expected)
     }
 
     #[test]
     pub fn test_parse_minus_operation() {
-        let b = lex("2-2".to_string());
+        let b = lex("2-2".to_string()).unwrap();
         let parser: &mut CalcParser = &mut init_calc_parser(&b);
         let expected = Ast::Node {
             value: Parameters::MinusOperation,
             left: Box::new(Ast::new(Parameters::Int(2))),
             right: Box::new(Ast::new(Parameters::Int(2))),
         };
-        let result = parser.parse();
+        let result = parser.parse().unwrap();
         assert_eq!(result, expected)
     }
 
     #[test]
     pub fn test_parse_mult_operation() {
-        let b = lex("2*2".to_string());
+        let b = lex("2*2".to_string()).unwrap();
         let parser: &mut CalcParser = &mut init_calc_parser(&b);
         let expected = Ast::Node {
             value: Parameters::MultiplicationOperation,
             left: Box::new(Ast::new(Parameters::Int(2))),
             right: Box::new(Ast::new(Parameters::Int(2))),
         };
-        let result = parser.parse();
+        let result = parser.parse().unwrap();
         assert_eq!(result, expected)
     }
 
     #[test]
     pub fn test_parse_mult_divide_operation() {
-        let b = lex("2*2/2".to_string());
+        let b = lex("2*2/2".to_string()).unwrap();
         let parser: &mut CalcParser = &mut init_calc_parser(&b);
         let expected = Ast::Node {
             value: Parameters::DivideOperation,
@@ -290,13 +527,13 @@ mod test {
             }),
             right: Box::from(Ast::new(Parameters::Int(2))),
         };
-        let result = parser.parse();
+        let result = parser.parse().unwrap();
         assert_eq!(result, expected)
     }
 
     #[test]
     pub fn test_parse_hard_mult_operation() {
-        let b = lex("2*2*2".to_string());
+        let b = lex("2*2*2".to_string()).unwrap();
         let parser: &mut CalcParser = &mut init_calc_parser(&b);
         let expected = Ast::Node {
             value: Parameters::MultiplicationOperation,
@@ -307,51 +544,51 @@ mod test {
             }),
             right: Box::from(Ast::new(Parameters::Int(2))),
         };
-        let result = parser.parse();
+        let result = parser.parse().unwrap();
         assert_eq!(result, expected)
     }
 
     #[test]
     pub fn test_parse_divide_operation() {
-        let b = lex("2/2".to_string());
+        let b = lex("2/2".to_string()).unwrap();
         let parser: &mut CalcParser = &mut init_calc_parser(&b);
         let expected = Ast::Node {
             value: Parameters::DivideOperation,
             left: Box::new(Ast::new(Parameters::Int(2))),
             right: Box::new(Ast::new(Parameters::Int(2))),
         };
-        let result = parser.parse();
+        let result = parser.parse().unwrap();
         assert_eq!(result, expected)
     }
 
     #[test]
     pub fn test_or_operation() {
-        let b = lex("false || true".to_string());
+        let b = lex("false || true".to_string()).unwrap();
         let parser = &mut init_calc_parser(&b);
         let expected = Ast::Node {
             value: Parameters::OrOperation,
             left: Box::new(Ast::new(Parameters::Bool(false))),
             right: Box::new(Ast::new(Parameters::Bool(true))),
         };
-        assert_eq!(parser.parse(), expected);
+        assert_eq!(parser.parse().unwrap(), expected);
     }
 
     #[test]
     pub fn test_assignment() {
-        let b = lex("i=1".to_string());
+        let b = lex("i=1".to_string()).unwrap();
         let parser: &mut CalcParser = &mut init_calc_parser(&b);
         let expected = Ast::Node {
             value: Parameters::Assign,
             left: Box::new(Ast::new(Parameters::Identifier("i".to_string()))),
             right: Box::new(Ast::new(Parameters::Int(1))),
         };
-        let result = parser.parse();
+        let result = parser.parse().unwrap();
         assert_eq!(result, expected);
     }
 
     #[test]
     pub fn simple_parenthesis() {
-        let b = lex("1+(1*1)".to_string());
+        let b = lex("1+(1*1)".to_string()).unwrap();
         let parser: &mut CalcParser = &mut init_calc_parser(&b);
         let expected = Ast::Node {
             value: Parameters::PlusOperation,
@@ -362,13 +599,13 @@ mod test {
                 right: Box::new(Ast::new(Parameters::Int(1))),
             }),
         };
-        let result = parser.parse();
+        let result = parser.parse().unwrap();
         assert_eq!(result, expected)
     }
 
     #[test]
     pub fn hard_parenthesis() {
-        let b = lex("1+(1*(1/1))".to_string());
+        let b = lex("1+(1*(1/1))".to_string()).unwrap();
         let parser: &mut CalcParser = &mut init_calc_parser(&b);
         let expected = Ast::Node {
             value: Parameters::PlusOperation,
@@ -383,13 +620,13 @@ mod test {
                 }),
             }),
         };
-        let result = parser.parse();
+        let result = parser.parse().unwrap();
         assert_eq!(result, expected)
     }
 
     #[test]
     pub fn without_parenthesis() {
-        let b = lex("1+1*1".to_string());
+        let b = lex("1+1*1".to_string()).unwrap();
         let parser: &mut CalcParser = &mut init_calc_parser(&b);
         let expected = Ast::Node {
             value: Parameters::PlusOperation,
@@ -400,13 +637,13 @@ mod test {
                 right: Box::new(Ast::new(Parameters::Int(1))),
             }),
         };
-        let result = parser.parse();
+        let result = parser.parse().unwrap();
         assert_eq!(result, expected)
     }
 
     #[test]
     pub fn hard_without_parenthesis() {
-        let b = lex("1+1*1/1".to_string());
+        let b = lex("1+1*1/1".to_string()).unwrap();
         let parser: &mut CalcParser = &mut init_calc_parser(&b);
         //1+((1*1)/1)
         let expected = Ast::Node {
@@ -422,13 +659,13 @@ mod test {
                 right: Box::from(Ast::new(Parameters::Int(1))),
             }),
         };
-        let result = parser.parse();
+        let result = parser.parse().unwrap();
         assert_eq!(result, expected)
     }
 
     #[test]
     pub fn test_left_priority() {
-        let b = lex("1+2*2".to_string());
+        let b = lex("1+2*2".to_string()).unwrap();
         let parser: &mut CalcParser = &mut init_calc_parser(&b);
         let expected = Ast::Node {
             value: Parameters::PlusOperation,
@@ -439,13 +676,13 @@ mod test {
                 right: Box::from(Ast::new(Parameters::Int(2))),
             }),
         };
-        let result = parser.parse();
+        let result = parser.parse().unwrap();
         assert_eq!(result, expected)
     }
 
     #[test]
     pub fn test_right_priority() {
-        let b = lex("2*2+1".to_string());
+        let b = lex("2*2+1".to_string()).unwrap();
         let parser = &mut init_calc_parser(&b);
         let expected = Ast::Node {
             value: Parameters::PlusOperation,
@@ -456,31 +693,423 @@ mod test {
             }),
             right: Box::from(Ast::new(Parameters::Int(1))),
         };
-        let result = parser.parse();
+        let result = parser.parse().unwrap();
         assert_eq!(result, expected)
     }
 
     #[test]
     pub fn test_vector_parsing() {
-        let b = lex("[2,2,2,2]".to_string());
+        let b = lex("[2,2,2,2]".to_string()).unwrap();
         let parser = &mut init_calc_parser(&b);
         let expected = Ast::Node {
             value: Parameters::Vector(Box::new(vec![Ast::new(Parameters::Int(2)); 4])),
             left: Box::new(Ast::Nil),
             right: Box::new(Ast::Nil),
         };
-        assert_eq!(parser.parse(), expected);
+        assert_eq!(parser.parse().unwrap(), expected);
     }
 
     #[test]
     pub fn test_string_parsing() {
-        let b = lex("\"test 1 2 1 2\"".to_string());
+        let b = lex("\"test 1 2 1 2\"".to_string()).unwrap();
         let parser = &mut init_calc_parser(&b);
         let expected = Ast::Node {
             value: Parameters::Str("test 1 2 1 2".to_string()),
             left: Box::new(Ast::Nil),
             right: Box::new(Ast::Nil),
         };
-        assert_eq!(parser.parse(), expected);
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+
+    #[test]
+    pub fn test_unexpected_token() {
+        let b = lex("1 + )".to_string()).unwrap();
+        let parser = &mut init_calc_parser(&b);
+        let expected = ParseError::new(
+            ParseErrorKind::UnexpectedToken(Token::RPAR),
+            Span::new(4, 5),
+        );
+        assert_eq!(parser.parse(), Err(expected));
+    }
+
+    #[test]
+    pub fn test_unexpected_end() {
+        let b = lex("2 *".to_string()).unwrap();
+        let parser = &mut init_calc_parser(&b);
+        let expected = ParseError::new(ParseErrorKind::UnexpectedEnd, Span::new(3, 3));
+        assert_eq!(parser.parse(), Err(expected));
+    }
+
+    #[test]
+    pub fn test_unclosed_parenthesis() {
+        let b = lex("2*(1+1".to_string()).unwrap();
+        let parser = &mut init_calc_parser(&b);
+        let expected = ParseError::new(ParseErrorKind::Unclosed('('), Span::new(2, 3));
+        assert_eq!(parser.parse(), Err(expected));
+    }
+
+    #[test]
+    pub fn test_unclosed_call() {
+        let b = lex("sin(".to_string()).unwrap();
+        let expected = ParseError::new(ParseErrorKind::Unclosed('('), Span::new(3, 4));
+        assert_eq!(init_calc_parser(&b).parse(), Err(expected));
+        let b = lex("(".to_string()).unwrap();
+        let expected = ParseError::new(ParseErrorKind::Unclosed('('), Span::new(0, 1));
+        assert_eq!(init_calc_parser(&b).parse(), Err(expected));
+    }
+
+    #[test]
+    pub fn test_expected_arrow() {
+        let b = lex("(1,2)".to_string()).unwrap();
+        let expected = ParseError::new(ParseErrorKind::ExpectedArrow, Span::new(0, 5));
+        assert_eq!(init_calc_parser(&b).parse(), Err(expected));
+    }
+
+    #[test]
+    pub fn test_no_prefix_product() {
+        let b = lex("1 + * 2".to_string()).unwrap();
+        let expected = ParseError::new(
+            ParseErrorKind::UnexpectedToken(Token::OPE(Operator::MULTIPLICATION)),
+            Span::new(4, 5),
+        );
+        assert_eq!(init_calc_parser(&b).parse(), Err(expected));
+        let b = lex("/ 2".to_string()).unwrap();
+        let expected = ParseError::new(
+            ParseErrorKind::UnexpectedToken(Token::OPE(Operator::DIVIDE)),
+            Span::new(0, 1),
+        );
+        assert_eq!(init_calc_parser(&b).parse(), Err(expected));
+    }
+
+    #[test]
+    pub fn test_unclosed_bracket() {
+        let b = lex("[1,2".to_string()).unwrap();
+        let parser = &mut init_calc_parser(&b);
+        let expected = ParseError::new(ParseErrorKind::Unclosed('['), Span::new(0, 1));
+        assert_eq!(parser.parse(), Err(expected));
+    }
+
+    #[test]
+    pub fn test_trailing_token() {
+        let b = lex("(1) 2".to_string()).unwrap();
+        let parser = &mut init_calc_parser(&b);
+        let expected = ParseError::new(
+            ParseErrorKind::UnexpectedToken(Token::INT(2)),
+            Span::new(4, 5),
+        );
+        assert_eq!(parser.parse(), Err(expected));
+    }
+
+    fn parse_str(input: &str) -> Ast {
+        let b = lex(input.to_string()).unwrap();
+        init_calc_parser(&b).parse().unwrap()
+    }
+
+    #[test]
+    pub fn test_precedence_regressions() {
+        let cases = [
+            ("2^3^2", "2^(3^2)"),
+            ("-2^2", "-(2^2)"),
+            ("-x+1", "(-x)+1"),
+            ("-x*y", "(-x)*y"),
+            ("2^-1", "2^(-1)"),
+            ("2*-3", "2*(-3)"),
+            ("1-2+3", "(1-2)+3"),
+            ("1-2-3", "(1-2)-3"),
+            ("8/2*2", "(8/2)*2"),
+            ("8/2/2", "(8/2)/2"),
+            ("1+2*3^2", "1+(2*(3^2))"),
+            ("!a && b", "(!a) && b"),
+            ("1+2 == 3", "(1+2) == 3"),
+            ("1 | 2 xor 3 & 4 << 1", "1 | (2 xor (3 & (4 << 1)))"),
+            ("~x+1", "(~x)+1"),
+            ("x = 1+2", "x = (1+2)"),
+            ("f(x)^2", "(f(x))^2"),
+        ];
+        for (input, grouped) in cases {
+            assert_eq!(parse_str(input), parse_str(grouped), "{input}");
+        }
+    }
+
+    #[test]
+    pub fn test_implicit_multiplication() {
+        let cases = [
+            ("2x", "2*x"),
+            ("3(x+1)", "3*(x+1)"),
+            ("(a+b)(a-b)", "(a+b)*(a-b)"),
+            ("2pi", "2*pi"),
+            ("x sin(x)", "x*sin(x)"),
+            ("2x^2", "2*(x^2)"),
+            ("-2x", "(-2)*x"),
+            ("1+2x", "1+(2*x)"),
+        ];
+        for (input, explicit) in cases {
+            assert_eq!(parse_str(input), parse_str(explicit), "{input}");
+        }
+    }
+
+    #[test]
+    pub fn test_call_is_not_implicit() {
+        let expected = Ast::Call {
+            name: "f".to_string(),
+            lst: vec![Ast::new(Parameters::Identifier("x".to_string()))],
+        };
+        assert_eq!(parse_str("f(x)"), expected);
+    }
+
+    #[test]
+    pub fn test_postfix_operators() {
+        let cases = [
+            ("5!", "fact(5)"),
+            ("2^3!", "2^fact(3)"),
+            ("-3!", "-fact(3)"),
+            ("(1+2)!", "fact(1+2)"),
+            ("50%", "50/100"),
+            ("2*10%", "2*(10/100)"),
+            ("f'", "diff(f)"),
+            ("f'(2)", "diff(f,2)"),
+        ];
+        for (input, expanded) in cases {
+            assert_eq!(parse_str(input), parse_str(expanded), "{input}");
+        }
+    }
+
+    #[test]
+    pub fn test_modulo_operators() {
+        let cases = [
+            ("7 % 3", "7 mod 3"),
+            ("2 * 7 % 3", "(2*7) mod 3"),
+            ("1 + 7 // 2", "1 + (7 // 2)"),
+            ("50% * 2", "(50/100) * 2"),
+            ("50% - 3", "(50/100) - 3"),
+            ("7 % -3", "7 mod (-3)"),
+            ("-7 % -3", "(-7) mod (-3)"),
+            ("x % -2", "x mod (-2)"),
+            ("x % ~1", "x mod (~1)"),
+            ("x % 0x10", "x mod 16"),
+            ("x % if y then 2 else 3", "x mod (if y then 2 else 3)"),
+            ("x % (y)", "x mod y"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_str(input), parse_str(expected), "{input}");
+        }
+    }
+
+    #[test]
+    pub fn test_parse_statements() {
+        let b = lex("a = 2; b = 3;; a*b".to_string()).unwrap();
+        let result = init_calc_parser(&b).parse_statements().unwrap();
+        let expected = vec![parse_str("a = 2"), parse_str("b = 3"), parse_str("a*b")];
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    pub fn test_parse_statements_error() {
+        let b = lex("1; 2 )".to_string()).unwrap();
+        let expected = ParseError::new(
+            ParseErrorKind::UnexpectedToken(Token::RPAR),
+            Span::new(5, 6),
+        );
+        assert_eq!(init_calc_parser(&b).parse_statements(), Err(expected));
+    }
+
+    #[test]
+    pub fn test_if_then_else() {
+        let expected = Ast::If {
+            condition: Box::from(parse_str("x < 0")),
+            then: Box::from(parse_str("-x")),
+            otherwise: Box::from(parse_str("x + 1")),
+        };
+        assert_eq!(parse_str("if x < 0 then -x else x + 1"), expected);
+    }
+
+    #[test]
+    pub fn test_if_missing_then() {
+        let b = lex("if x 1".to_string()).unwrap();
+        let expected = ParseError::new(
+            ParseErrorKind::UnexpectedToken(Token::INT(1)),
+            Span::new(5, 6),
+        );
+        assert_eq!(init_calc_parser(&b).parse(), Err(expected));
+    }
+
+    #[test]
+    pub fn test_del() {
+        let expected = Ast::Node {
+            value: Parameters::Delete("f".to_string()),
+            left: Box::from(Ast::Nil),
+            right: Box::from(Ast::Nil),
+        };
+        assert_eq!(parse_str("del f"), expected);
+        let expected = Ast::Node {
+            value: Parameters::Memo("fib".to_string()),
+            left: Box::from(Ast::Nil),
+            right: Box::from(Ast::Nil),
+        };
+        assert_eq!(parse_str("memo fib"), expected);
+
+        let b = lex("del 3".to_string()).unwrap();
+        let expected = ParseError::new(
+            ParseErrorKind::UnexpectedToken(Token::INT(3)),
+            Span::new(4, 5),
+        );
+        assert_eq!(init_calc_parser(&b).parse(), Err(expected));
+    }
+
+    #[test]
+    pub fn test_lambda() {
+        let lambda = |params: &[&str], body: &str| Ast::Node {
+            value: Parameters::Lambda(Box::from(Lambda {
+                params: params.iter().map(|x| x.to_string()).collect(),
+                body: parse_str(body),
+                captured: HashMap::new(),
+            })),
+            left: Box::from(Ast::Nil),
+            right: Box::from(Ast::Nil),
+        };
+        assert_eq!(parse_str("x -> x^2 + 1"), lambda(&["x"], "x^2 + 1"));
+        assert_eq!(parse_str("(a, b) -> a*b"), lambda(&["a", "b"], "a*b"));
+        let expected = Ast::Call {
+            name: "map".to_string(),
+            lst: vec![lambda(&["x"], "2x"), parse_str("v")],
+        };
+        assert_eq!(parse_str("map(x -> 2x, v)"), expected);
+        let expected = Ast::Call {
+            name: "apply".to_string(),
+            lst: vec![lambda(&["x"], "x + 1"), parse_str("[2]")],
+        };
+        assert_eq!(parse_str("(x -> x + 1)(2)"), expected);
+        assert_eq!(parse_str("add(1)(2)"), parse_str("apply(add(1), [2])"));
+
+        let b = lex("(x, 1) -> x".to_string()).unwrap();
+        let expected = ParseError::new(
+            ParseErrorKind::UnexpectedToken(Token::ARROW),
+            Span::new(7, 9),
+        );
+        assert_eq!(init_calc_parser(&b).parse(), Err(expected));
+    }
+
+    #[test]
+    pub fn test_index_slice_and_range() {
+        let call = |name: &str, lst: Vec<Ast>| Ast::Call {
+            name: name.to_string(),
+            lst,
+        };
+        let expected = call(
+            "index",
+            vec![
+                call("index", vec![parse_str("m"), parse_str("i")]),
+                parse_str("j + 1"),
+            ],
+        );
+        assert_eq!(parse_str("m[i][j + 1]"), expected);
+        let expected = call("slice", vec![parse_str("v"), Ast::Nil, parse_str("-1")]);
+        assert_eq!(parse_str("v[:-1]"), expected);
+        let expected = call("range", vec![parse_str("1"), parse_str("n + 1")]);
+        assert_eq!(parse_str("1..n + 1"), expected);
+    }
+
+    #[test]
+    pub fn test_blocks_and_loops() {
+        let expected = Ast::Block(vec![parse_str("a = 1"), parse_str("a + 1")]);
+        assert_eq!(parse_str("{ a = 1;\n a + 1; }"), expected);
+        assert_eq!(parse_str("{}"), Ast::Block(vec![]));
+        let expected = Ast::For {
+            variable: "i".to_string(),
+            iterable: Box::from(parse_str("1..n")),
+            body: Box::from(parse_str("{s = s + i}")),
+        };
+        assert_eq!(parse_str("for i in 1..n { s = s + i }"), expected);
+        let expected = Ast::While {
+            condition: Box::from(parse_str("x > 1")),
+            body: Box::from(parse_str("{x = x / 2}")),
+        };
+        assert_eq!(parse_str("while x > 1 { x = x / 2 }"), expected);
+    }
+
+    #[test]
+    pub fn test_unclosed_brace() {
+        let b = lex("{1; 2".to_string()).unwrap();
+        let parser = &mut init_calc_parser(&b);
+        let expected = ParseError::new(ParseErrorKind::Unclosed('{'), Span::new(0, 1));
+        assert_eq!(parser.parse(), Err(expected));
+    }
+
+    #[test]
+    pub fn test_compound_assignment() {
+        let expected = Ast::Node {
+            value: Parameters::CompoundAssign(Box::from(Parameters::PlusOperation)),
+            left: Box::from(parse_str("x")),
+            right: Box::from(parse_str("2y")),
+        };
+        assert_eq!(parse_str("x += 2y"), expected);
+        let expected = Ast::Node {
+            value: Parameters::Assign,
+            left: Box::from(parse_str("[a, b]")),
+            right: Box::from(parse_str("[b, a]")),
+        };
+        assert_eq!(parse_str("[a, b] = [b, a]"), expected);
+    }
+
+    #[test]
+    pub fn test_invalid_assignment_target() {
+        let cases = [
+            ("2x = 3", Span::new(3, 4)),
+            ("f(x) *= 2", Span::new(5, 7)),
+            ("v[0] = 5", Span::new(5, 6)),
+            ("1..3 = 4", Span::new(5, 6)),
+            ("f'(x) = 3", Span::new(6, 7)),
+            ("f(x + y) = 1", Span::new(9, 10)),
+            ("v[0] when v > 0 = 1", Span::new(5, 9)),
+            ("5! = 3", Span::new(3, 4)),
+        ];
+        for (input, span) in cases {
+            let b = lex(input.to_string()).unwrap();
+            let parser = &mut init_calc_parser(&b);
+            let expected = ParseError::new(ParseErrorKind::InvalidAssignmentTarget, span);
+            assert_eq!(parser.parse(), Err(expected), "{input}");
+        }
+        assert!(
+            init_calc_parser(&lex("f(0, -1, n=2, ...a) = 1".to_string()).unwrap())
+                .parse()
+                .is_ok()
+        );
+        let b = lex("a = b = 3".to_string()).unwrap();
+        let expected = ParseError::new(ParseErrorKind::ChainedAssignment, Span::new(2, 3));
+        assert_eq!(init_calc_parser(&b).parse(), Err(expected));
+    }
+
+    #[test]
+    pub fn test_guarded_definition() {
+        let expected = Ast::Node {
+            value: Parameters::Assign,
+            left: Box::from(Ast::Node {
+                value: Parameters::When,
+                left: Box::from(parse_str("sign(x)")),
+                right: Box::from(parse_str("x < 0")),
+            }),
+            right: Box::from(parse_str("-1")),
+        };
+        assert_eq!(parse_str("sign(x) when x < 0 = -1"), expected);
+        let b = lex("x when x > 0 = 1".to_string()).unwrap();
+        let expected = ParseError::new(ParseErrorKind::InvalidAssignmentTarget, Span::new(2, 6));
+        assert_eq!(init_calc_parser(&b).parse(), Err(expected));
+    }
+
+    #[test]
+    pub fn test_default_and_variadic_parameters() {
+        let expected = Ast::Call {
+            name: "f".to_string(),
+            lst: vec![
+                parse_str("x"),
+                parse_str("n = 2"),
+                Ast::Node {
+                    value: Parameters::Variadic("args".to_string()),
+                    left: Box::from(Ast::Nil),
+                    right: Box::from(Ast::Nil),
+                },
+            ],
+        };
+        assert_eq!(parse_str("f(x, n=2, ...args)"), expected);
     }
 }
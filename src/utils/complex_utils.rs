@@ -0,0 +1,78 @@
+use num_complex::Complex64;
+
+use crate::exact_math::complex_mode::ComplexMode;
+use crate::parsing::ast::Parameters;
+use crate::COMPLEX_MODE;
+
+/// Wraps a complex number, going back to a `Float` whenever the imaginary
+/// part vanishes. Components which are only rounding noise compared to the
+/// modulus (e.g. `exp(i*pi)`) are dropped.
+pub fn from_complex(c: Complex64) -> Parameters {
+    let noise = c.norm() * f64::EPSILON;
+    let re = if c.re.abs() <= noise { 0.0 } else { c.re };
+    let im = if c.im.abs() <= noise { 0.0 } else { c.im };
+    if im == 0.0 {
+        Parameters::Float(re)
+    } else {
+        Parameters::Complex(Complex64::new(re, im))
+    }
+}
+
+/// Formats a complex number in the current complex mode, each component is
+/// printed following the float mode.
+pub fn complex_to_string(c: &Complex64) -> String {
+    let component = |f: f64| Parameters::Float(f).to_string();
+    COMPLEX_MODE.with(|cm| match *cm.borrow() {
+        ComplexMode::Polar => format!("{}∠{}", component(c.norm()), component(c.arg())),
+        ComplexMode::Rectangular => {
+            let im = if c.im.abs() == 1.0 {
+                "i".to_string()
+            } else {
+                format!("{}i", component(c.im.abs()))
+            };
+            let sign = if c.im < 0.0 { "-" } else { "+" };
+            if c.re == 0.0 {
+                format!("{}{im}", sign.trim_start_matches('+'))
+            } else {
+                format!("{}{sign}{im}", component(c.re))
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use num_complex::Complex64;
+
+    use crate::exact_math::complex_mode::ComplexMode;
+    use crate::parsing::ast::Parameters;
+    use crate::COMPLEX_MODE;
+
+    use super::{complex_to_string, from_complex};
+
+    #[test]
+    pub fn test_from_complex_demotes() {
+        assert_eq!(
+            from_complex(Complex64::new(2.0, 0.0)),
+            Parameters::Float(2.0)
+        );
+        assert_eq!(
+            from_complex(Complex64::new(-1.0, 1e-16)),
+            Parameters::Float(-1.0)
+        );
+    }
+
+    #[test]
+    pub fn test_rectangular() {
+        assert_eq!(complex_to_string(&Complex64::new(1.0, -2.0)), "1-2i");
+        assert_eq!(complex_to_string(&Complex64::new(0.0, 1.0)), "i");
+    }
+
+    #[test]
+    pub fn test_polar() {
+        COMPLEX_MODE.with(|cm| *cm.borrow_mut() = ComplexMode::Polar);
+        let result = complex_to_string(&Complex64::new(0.0, 2.0));
+        COMPLEX_MODE.with(|cm| *cm.borrow_mut() = ComplexMode::Rectangular);
+        assert_eq!(result, "2∠1.5707963267948966");
+    }
+}
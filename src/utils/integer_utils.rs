@@ -1,9 +1,87 @@
-pub fn gcd(mut n: i64, mut m: i64) -> i64 {
-    while m != 0 {
-        if m < n {
-            std::mem::swap(&mut m, &mut n);
-        }
-        m %= n;
-    }
-    n
+use num_bigint::BigInt;
+use num_traits::{Signed, ToPrimitive};
+
+use crate::exact_math::radix_mode::RadixMode;
+use crate::parsing::ast::Parameters;
+use crate::RADIX_MODE;
+
+/// Wraps an arbitrary precision integer, going back to `Int` whenever the
+/// value fits in an i64.
+pub fn from_big(b: BigInt) -> Parameters {
+    match b.to_i64() {
+        Some(i) => Parameters::Int(i),
+        None => Parameters::Big(b),
+    }
+}
+
+/// Formats an integer in the given base with its `0x`, `0b` or `0o` prefix, the
+/// sign is kept in front of the prefix.
+pub fn to_radix_string(b: &BigInt, radix: &RadixMode) -> String {
+    let sign = if b.is_negative() { "-" } else { "" };
+    let magnitude = b.abs();
+    match radix {
+        RadixMode::Decimal => b.to_string(),
+        RadixMode::Hexadecimal => format!("{sign}0x{magnitude:x}"),
+        RadixMode::Binary => format!("{sign}0b{magnitude:b}"),
+        RadixMode::Octal => format!("{sign}0o{magnitude:o}"),
+    }
+}
+
+/// Formats an integer following the current radix mode.
+pub fn int_to_string(b: &BigInt) -> String {
+    RADIX_MODE.with(|rm| to_radix_string(b, &rm.borrow()))
+}
+
+pub fn big_to_f64(b: &BigInt) -> f64 {
+    b.to_f64().unwrap_or(f64::NAN)
+}
+
+/// Exponentiation of big integers, `None` is returned when the result would
+/// be unreasonably large.
+pub fn checked_pow(base: &BigInt, exponent: u32) -> Option<BigInt> {
+    const MAX_BITS: u64 = 1 << 20;
+    if base.bits() * (exponent as u64) > MAX_BITS && base.bits() > 1 {
+        return None;
+    }
+    Some(num_traits::pow(base.clone(), exponent as usize))
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigInt;
+
+    use crate::parsing::ast::Parameters;
+
+    use crate::exact_math::radix_mode::RadixMode;
+
+    use super::{checked_pow, from_big, to_radix_string};
+
+    #[test]
+    pub fn test_from_big_demotes() {
+        assert_eq!(from_big(BigInt::from(42)), Parameters::Int(42));
+    }
+
+    #[test]
+    pub fn test_checked_pow() {
+        let expected: BigInt = "1267650600228229401496703205376".parse().unwrap();
+        let result = checked_pow(&BigInt::from(2), 100);
+        assert_eq!(result, Some(expected));
+    }
+
+    #[test]
+    pub fn test_checked_pow_too_large() {
+        assert_eq!(checked_pow(&BigInt::from(10), 1_000_000), None);
+    }
+
+    #[test]
+    pub fn test_to_radix_string() {
+        assert_eq!(
+            to_radix_string(&BigInt::from(31), &RadixMode::Hexadecimal),
+            "0x1f"
+        );
+        assert_eq!(
+            to_radix_string(&BigInt::from(-5), &RadixMode::Binary),
+            "-0b101"
+        );
+    }
 }
@@ -1,3 +1,4 @@
+pub mod complex_utils;
 pub mod integer_utils;
 pub mod matrix_utils;
 pub mod plot_utils;